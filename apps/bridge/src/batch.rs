@@ -0,0 +1,142 @@
+//! Coalescing of outbound frames into multi-frame PDUs.
+//!
+//! A PDU is a concatenation of `jseries::push_frame` records (`[u16 big-endian length][frame
+//! bytes]`), so the receiver can split it with `jseries::split_frames` regardless of whether
+//! individual frames are sealed.
+
+use std::time::{Duration, Instant};
+
+/// Batching state machine; the caller supplies the clock so it can be driven in tests.
+#[derive(Debug)]
+pub struct Batcher {
+    window: Duration,
+    max: usize,
+    max_bytes: usize,
+    pdu: Vec<u8>,
+    count: usize,
+    opened_at: Option<Instant>,
+}
+
+impl Batcher {
+    /// Flush after `max` frames or once `window` has elapsed since the first queued frame,
+    /// and before a frame would grow the PDU past `max_bytes`.
+    pub fn new(window: Duration, max: usize, max_bytes: usize) -> Self {
+        Self {
+            window,
+            max: max.max(1),
+            max_bytes,
+            pdu: Vec::new(),
+            count: 0,
+            opened_at: None,
+        }
+    }
+
+    /// Queue `frame`, returning a PDU if this push filled the batch. A frame that would
+    /// overflow `max_bytes` first flushes the frames queued before it and opens the next
+    /// batch; one too long for the length prefix is an error and leaves the batch unchanged.
+    pub fn push(&mut self, frame: &[u8], now: Instant) -> Result<Option<Vec<u8>>, jseries::Error> {
+        let mut record = Vec::with_capacity(2 + frame.len());
+        jseries::push_frame(&mut record, frame)?;
+        if self.count > 0 && self.pdu.len() + record.len() > self.max_bytes {
+            let full = self.take();
+            self.queue(&record, now);
+            return Ok(Some(full));
+        }
+        self.queue(&record, now);
+        Ok((self.count >= self.max).then(|| self.take()))
+    }
+
+    /// Flush if the window of the oldest queued frame has expired.
+    pub fn poll(&mut self, now: Instant) -> Option<Vec<u8>> {
+        let deadline = self.deadline()?;
+        (now >= deadline).then(|| self.take())
+    }
+
+    /// When the pending batch must be flushed, if any frames are queued.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.opened_at.map(|t| t + self.window)
+    }
+
+    /// Whatever is queued, regardless of the window (on shutdown).
+    pub fn flush(&mut self) -> Option<Vec<u8>> {
+        (self.count > 0).then(|| self.take())
+    }
+
+    fn queue(&mut self, record: &[u8], now: Instant) {
+        self.pdu.extend_from_slice(record);
+        self.opened_at.get_or_insert(now);
+        self.count += 1;
+    }
+
+    fn take(&mut self) -> Vec<u8> {
+        self.count = 0;
+        self.opened_at = None;
+        std::mem::take(&mut self.pdu)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jseries::split_frames as split;
+
+    #[test]
+    fn flushes_when_count_reached() {
+        let t0 = Instant::now();
+        let mut b = Batcher::new(Duration::from_millis(50), 3, 1472);
+        assert_eq!(b.push(b"a", t0).unwrap(), None);
+        assert_eq!(b.push(b"bb", t0 + Duration::from_millis(1)).unwrap(), None);
+        let pdu = b
+            .push(b"ccc", t0 + Duration::from_millis(2))
+            .unwrap()
+            .unwrap();
+        assert_eq!(split(&pdu).unwrap(), vec![&b"a"[..], b"bb", b"ccc"]);
+        assert_eq!(b.deadline(), None);
+        assert_eq!(b.poll(t0 + Duration::from_secs(1)), None);
+        assert_eq!(b.flush(), None);
+    }
+
+    #[test]
+    fn flushes_when_window_expires() {
+        let t0 = Instant::now();
+        let mut b = Batcher::new(Duration::from_millis(50), 10, 1472);
+        assert_eq!(b.push(b"a", t0).unwrap(), None);
+        assert_eq!(b.push(b"b", t0 + Duration::from_millis(30)).unwrap(), None);
+        assert_eq!(b.deadline(), Some(t0 + Duration::from_millis(50)));
+        assert_eq!(b.poll(t0 + Duration::from_millis(49)), None);
+        let pdu = b.poll(t0 + Duration::from_millis(50)).unwrap();
+        assert_eq!(split(&pdu).unwrap(), vec![&b"a"[..], b"b"]);
+
+        // The next window starts with the next frame, not the last flush.
+        assert_eq!(b.push(b"c", t0 + Duration::from_millis(200)).unwrap(), None);
+        assert_eq!(b.deadline(), Some(t0 + Duration::from_millis(250)));
+    }
+
+    #[test]
+    fn flushes_before_exceeding_max_bytes() {
+        let t0 = Instant::now();
+        // Two 3-byte records fit in 8 bytes; a third would make 9.
+        let mut b = Batcher::new(Duration::from_millis(50), 10, 8);
+        assert_eq!(b.push(b"a", t0).unwrap(), None);
+        assert_eq!(b.push(b"b", t0).unwrap(), None);
+        let later = t0 + Duration::from_millis(10);
+        let pdu = b.push(b"c", later).unwrap().unwrap();
+        assert_eq!(split(&pdu).unwrap(), vec![&b"a"[..], b"b"]);
+
+        // The frame that didn't fit opens the next batch.
+        assert_eq!(b.deadline(), Some(later + Duration::from_millis(50)));
+        assert_eq!(split(&b.flush().unwrap()).unwrap(), vec![&b"c"[..]]);
+    }
+
+    #[test]
+    fn frame_past_length_prefix_is_an_error() {
+        let t0 = Instant::now();
+        let mut b = Batcher::new(Duration::from_millis(50), 10, usize::MAX);
+        assert_eq!(b.push(b"a", t0).unwrap(), None);
+        assert!(matches!(
+            b.push(&vec![0; 70_000], t0),
+            Err(jseries::Error::ExceedsLimit { len: 70_000, .. })
+        ));
+        assert_eq!(split(&b.flush().unwrap()).unwrap(), vec![&b"a"[..]]);
+    }
+}
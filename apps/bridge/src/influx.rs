@@ -0,0 +1,174 @@
+//! InfluxDB sink: decoded tracks as line protocol, POSTed over plain HTTP/1.1.
+
+use anyhow::{anyhow, bail, Context, Result};
+use jseries::{J2_2Ppli, J3_2AirTrack, J3_3SurfaceTrack, J7_0TrackManagement, JMessage};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// Format one decoded message as a line-protocol point at `ts_ns` (`airtrack`, `ppli`, `surfacetrack` or `trackmgmt`).
+pub fn line(msg: &JMessage, ts_ns: u128) -> String {
+    let (track, identity) = match msg {
+        JMessage::J3_2(t) => (t, None),
+        JMessage::J3_2V2(t) => (&t.base, Some(t.identity())),
+        JMessage::J3_2V3(t) => (&t.base.base, Some(t.base.identity())),
+        JMessage::J2_2(p) => return ppli_line(p, ts_ns),
+        JMessage::J3_3(t) => return surface_line(t, ts_ns),
+        JMessage::J7_0(m) => return management_line(m, ts_ns),
+        // Kinds this sink doesn't model yet are still counted, tagged by message id.
+        _ => return format!("jmessage,kind={:#04x} count=1i {ts_ns}", msg.id()),
+    };
+    let mut tags = format!("airtrack,track={}", track.track);
+    if let Some(identity) = identity {
+        tags.push_str(&format!(",identity={identity}"));
+    }
+    format!("{tags} {} {ts_ns}", fields(track))
+}
+
+/// Own-ship PPLI reports go to their own `ppli` measurement.
+fn ppli_line(p: &J2_2Ppli, ts_ns: u128) -> String {
+    format!(
+        "ppli,track={},platform={} lat={:.6},lon={:.6},alt={:.0} {ts_ns}",
+        p.track_number,
+        p.platform_type,
+        p.lat_deg(),
+        p.lon_deg(),
+        p.alt_m()
+    )
+}
+
+/// Surface tracks have no altitude and go to a `surfacetrack` measurement.
+fn surface_line(t: &J3_3SurfaceTrack, ts_ns: u128) -> String {
+    format!(
+        "surfacetrack,track={} lat={:.7},lon={:.7},speed={}i,course={}i {ts_ns}",
+        t.track_number,
+        t.lat_deg(),
+        t.lon_deg(),
+        t.speed_ms,
+        t.course_cdeg
+    )
+}
+
+/// Track management events go to a `trackmgmt` measurement, tagged by action.
+fn management_line(m: &J7_0TrackManagement, ts_ns: u128) -> String {
+    format!(
+        "trackmgmt,track={},action={} receiving_unit={}i {ts_ns}",
+        m.track_number, m.action, m.receiving_unit
+    )
+}
+
+fn fields(t: &J3_2AirTrack) -> String {
+    format!(
+        "lat={:.6},lon={:.6},alt={:.0},speed={}i,heading={:.2}",
+        t.lat_deg(),
+        t.lon_deg(),
+        t.alt_m(),
+        t.speed_ms,
+        t.heading_cdeg as f64 / 100.0
+    )
+}
+
+/// A parsed `http://host[:port]/path?query` write endpoint.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Endpoint {
+    host: String,
+    port: u16,
+    path: String,
+}
+
+impl std::str::FromStr for Endpoint {
+    type Err = anyhow::Error;
+
+    fn from_str(url: &str) -> Result<Self> {
+        let rest = url
+            .strip_prefix("http://")
+            .ok_or_else(|| anyhow!("influx endpoint must be an http:// URL: {url}"))?;
+        let (authority, path) = match rest.find('/') {
+            Some(i) => rest.split_at(i),
+            None => (rest, "/"),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().context("bad influx port")?),
+            None => (authority, 80),
+        };
+        if host.is_empty() {
+            bail!("influx endpoint has no host: {url}");
+        }
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+        })
+    }
+}
+
+/// POST `body` to `endpoint`, failing on a non-2xx status.
+pub async fn post(endpoint: &Endpoint, token: Option<&str>, body: &str) -> Result<()> {
+    let mut stream = TcpStream::connect((endpoint.host.as_str(), endpoint.port)).await?;
+    let mut request = format!(
+        "POST {} HTTP/1.1\r\nHost: {}:{}\r\nContent-Type: text/plain; charset=utf-8\r\n\
+         Content-Length: {}\r\nConnection: close\r\n",
+        endpoint.path,
+        endpoint.host,
+        endpoint.port,
+        body.len()
+    );
+    if let Some(token) = token {
+        request.push_str(&format!("Authorization: Token {token}\r\n"));
+    }
+    request.push_str("\r\n");
+    request.push_str(body);
+    stream.write_all(request.as_bytes()).await?;
+
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).await?;
+    let status_line = response
+        .split(|&b| b == b'\n')
+        .next()
+        .map(String::from_utf8_lossy)
+        .unwrap_or_default();
+    let status: u16 = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| anyhow!("malformed influx response: {status_line:?}"))?;
+    if !(200..300).contains(&status) {
+        bail!("influx write failed: {}", status_line.trim_end());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jseries::{Identity, J3_2AirTrackV2};
+
+    #[test]
+    fn formats_known_track() {
+        let heading_cdeg = jseries::units::deg_to_cdeg(271.0);
+        let t = J3_2AirTrack::from_geo(42, 45.0, -122.0, 1500.0, 220, heading_cdeg);
+        assert_eq!(
+            line(&JMessage::J3_2(t.clone()), 1_700_000_000_000_000_000),
+            "airtrack,track=42 lat=44.999914,lon=-122.000317,alt=1501,speed=220i,heading=271.00 1700000000000000000"
+        );
+        let v2 = J3_2AirTrackV2::new(t, Identity::Friend);
+        assert!(
+            line(&JMessage::J3_2V2(v2), 0).starts_with("airtrack,track=42,identity=friend lat=")
+        );
+    }
+
+    #[test]
+    fn parses_endpoint() {
+        let e: Endpoint = "http://localhost:8086/api/v2/write?bucket=ads"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            e,
+            Endpoint {
+                host: "localhost".into(),
+                port: 8086,
+                path: "/api/v2/write?bucket=ads".into(),
+            }
+        );
+        assert!("https://localhost/".parse::<Endpoint>().is_err());
+    }
+}
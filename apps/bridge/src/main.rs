@@ -1,7 +1,26 @@
-use anyhow::Result;
-use clap::Parser;
-use jseries::{J3_2AirTrack, JMessage};
+use anyhow::{Context, Result};
+use clap::{Parser, ValueEnum};
+use jseries::{Identity, J3_2AirTrack, J3_2AirTrackV2, JMessage, Precision};
+use std::future::Future;
 use std::net::SocketAddr;
+use std::sync::atomic::Ordering::Relaxed;
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, warn};
+
+mod batch;
+mod influx;
+mod metrics;
+#[cfg(feature = "pcap")]
+mod pcap;
+mod ratelimit;
+mod record;
+mod reverse;
+mod source;
+mod transform;
+
+use metrics::Metrics;
+use source::{Inbound, Source};
+use transform::Pipeline;
 
 #[derive(Debug, Parser)]
 #[command(name = "bridge", about = "ADS Secure Translator bridge (prototype)")]
@@ -12,81 +31,1078 @@ struct Args {
     /// UDP sink address for Link 16 bytes (e.g., 127.0.0.1:5000)
     #[arg(long, default_value = "127.0.0.1:5000")]
     sink: SocketAddr,
-    /// Use E2EE with PSK hex (optional)
+    /// Where decoded tracks go: sealed J-Series frames over UDP, or InfluxDB line protocol
+    #[arg(long, value_enum, default_value_t = Transport::Udp)]
+    transport: Transport,
+    /// InfluxDB write endpoint for `--transport influx`
+    #[arg(
+        long,
+        default_value = "http://127.0.0.1:8086/api/v2/write?bucket=ads&precision=ns"
+    )]
+    influx_url: influx::Endpoint,
+    /// InfluxDB API token, sent as `Authorization: Token ...`
+    #[arg(long)]
+    influx_token: Option<String>,
+    /// Use E2EE with PSK hex (optional): seals outbound frames and opens inbound payloads
+    /// that aren't plain JSON (sealed with AAD `telemetry`, as `publisher --psk-hex` does)
     #[arg(long)]
     psk_hex: Option<String>,
+    /// Shift latitudes by this many degrees before encoding
+    #[arg(long)]
+    offset_lat: Option<f64>,
+    /// Shift longitudes by this many degrees before encoding
+    #[arg(long)]
+    offset_lon: Option<f64>,
+    /// Forward only these tracks, e.g. `42,100,255` (all tracks when absent)
+    #[arg(long, value_delimiter = ',')]
+    only_tracks: Vec<u16>,
+    /// Renumber a track before encoding, as FROM=TO (repeatable)
+    #[arg(long, value_parser = transform::parse_remap)]
+    remap_track: Vec<(u16, u16)>,
+    /// Drop telemetry with a heading outside [0, 360) instead of wrapping it
+    #[arg(long)]
+    strict_heading: bool,
+    /// Reduce positions to a grid of this size (m) before encoding, for privacy
+    #[arg(long)]
+    coarse_grid_m: Option<f64>,
+    /// Bind the sample's encoding and attachment into the AAD (receiver must rebuild it
+    /// with `e2ee::bind_aad(&[b"j3.2", encoding, attachment])`)
+    #[arg(long)]
+    aad_from_attachment: bool,
+    /// Coalesce frames arriving within this window (ms) into one length-prefixed PDU
+    #[arg(long, visible_alias = "batch-ms")]
+    batch_window_ms: Option<u64>,
+    /// Flush a batch early once it holds this many frames (default 16); given alone it
+    /// enables batching with a 50 ms window
+    #[arg(long, visible_alias = "batch")]
+    batch_max: Option<usize>,
+    /// Flush a batch early rather than grow its PDU past this many bytes (1472 fits one
+    /// 1500-byte Ethernet MTU)
+    #[arg(long, default_value_t = 1472)]
+    batch_max_bytes: usize,
+    /// Limit datagrams sent to the sink to this many per second
+    #[arg(long, value_parser = ratelimit::parse_rate)]
+    max_rate: Option<f64>,
+    /// What happens to datagrams over --max-rate
+    #[arg(long, value_enum, default_value_t = ratelimit::OverRate::Drop)]
+    over_rate: ratelimit::OverRate,
+    /// Append every datagram sent to the sink, with its send time, to this file
+    #[arg(long)]
+    record: Option<std::path::PathBuf>,
+    /// Re-send the datagrams of a `--record` file to --sink with their original timing, then
+    /// exit
+    #[arg(long, conflicts_with = "record")]
+    replay: Option<std::path::PathBuf>,
+    /// Translate and send a single sample, then exit (nonzero on timeout)
+    #[arg(long)]
+    once: bool,
+    /// How long `--once` waits for a sample
+    #[arg(long, default_value_t = 5000)]
+    once_timeout_ms: u64,
+    /// Reverse direction: decode J-Series frames arriving on --listen and publish them as
+    /// telemetry JSON on --publish (opened with --psk-hex when set)
+    #[arg(long)]
+    reverse: bool,
+    /// UDP address `--reverse` listens on
+    #[arg(long, default_value = "0.0.0.0:5000")]
+    listen: SocketAddr,
+    /// Zenoh key `--reverse` publishes telemetry to
+    #[arg(long, default_value = "link16/telemetry")]
+    publish: String,
+    /// Serve Prometheus metrics on `http://<addr>/metrics`
+    #[arg(long)]
+    metrics_addr: Option<SocketAddr>,
+    /// Most verbose log level shown (error, warn, info, debug or trace)
+    #[arg(long, default_value_t = tracing::Level::INFO)]
+    log_level: tracing::Level,
+    /// Replay UDP payloads from a pcap capture instead of subscribing to Zenoh
+    #[cfg(feature = "pcap")]
+    #[arg(long)]
+    pcap: Option<std::path::PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Transport {
+    Udp,
+    Influx,
 }
 
-#[derive(Debug, serde::Deserialize)]
+/// Base AAD for sealed J3.2 frames.
+const AAD_J3_2: &[u8] = b"j3.2";
+/// AAD for sealed inbound telemetry JSON.
+const AAD_TELEMETRY: &[u8] = b"telemetry";
+
+/// Delay before the first Zenoh reconnect attempt, doubling up to `RECONNECT_MAX`.
+#[cfg(feature = "zenoh")]
+const RECONNECT_INITIAL: Duration = Duration::from_millis(250);
+#[cfg(feature = "zenoh")]
+const RECONNECT_MAX: Duration = Duration::from_secs(30);
+
+/// Batch window when only `--batch` is given.
+const BATCH_WINDOW: Duration = Duration::from_millis(50);
+
+/// Frames per batch when only `--batch-ms` is given.
+const BATCH_MAX: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 struct Telemetry {
     track: u16,
     lat: f64,
     lon: f64,
     alt_m: f64,
     speed_ms: u16,
-    heading_deg: u16,
+    heading_deg: f64,
+    /// Optional identity code (see `jseries::Identity`); selects J3.2 v2 when present.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    identity_code: Option<u8>,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
+    tracing_subscriber::fmt()
+        .with_max_level(args.log_level)
+        .init();
+    let pipeline = pipeline(&args);
+    let metrics = std::sync::Arc::new(Metrics::default());
+    if let Some(addr) = args.metrics_addr {
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        info!(%addr, "serving metrics");
+        tokio::spawn(metrics::serve(listener, metrics.clone()));
+    }
 
-    let sess = args.psk_hex.as_deref().map(hex_to_session);
-    let sock = tokio::net::UdpSocket::bind("0.0.0.0:0").await?;
+    let sess = args
+        .psk_hex
+        .as_deref()
+        .map(e2ee::session_from_psk_hex)
+        .transpose()
+        .context("--psk-hex")?;
+    #[cfg(feature = "pcap")]
+    if let Some(path) = &args.pcap {
+        return pcap::replay(path, sess.as_ref(), AAD_J3_2);
+    }
+    if let Some(path) = &args.replay {
+        let sock = tokio::net::UdpSocket::bind("0.0.0.0:0").await?;
+        record::replay(path, &sock, args.sink).await?;
+        return Ok(());
+    }
 
     #[cfg(feature = "zenoh")]
-    {
-        // Zenoh 1.x API: open() and declare_subscriber() are async and return Results directly.
-        let config = zenoh::config::Config::default();
-        let session = zenoh::open(config)
+    if args.reverse {
+        let sock = tokio::net::UdpSocket::bind(args.listen).await?;
+        let session = zenoh::open(zenoh::config::Config::default())
             .await
             .map_err(|e| anyhow::anyhow!("{}", e))?;
-        let sub = session
-            .declare_subscriber(args.subscribe.clone())
+        let publisher = session
+            .declare_publisher(args.publish.clone())
             .await
             .map_err(|e| anyhow::anyhow!("{}", e))?;
-        println!(
-            "bridge: listening on Zenoh selector '{}' -> UDP {}",
-            args.subscribe, args.sink
-        );
+        info!(listen = %args.listen, publish = %args.publish, "reverse bridge started");
         loop {
-            let sample = sub
-                .recv_async()
-                .await
-                .map_err(|e| anyhow::anyhow!("{}", e))?;
-            // Extract payload text from ZBytes
-            match sample.payload().try_to_string() {
-                Ok(text) => match serde_json::from_str::<Telemetry>(&text) {
-                    Ok(t) => {
-                        let j = JMessage::J3_2(J3_2AirTrack::from_geo(
-                            t.track,
-                            t.lat,
-                            t.lon,
-                            t.alt_m,
-                            t.speed_ms,
-                            t.heading_deg,
-                        ));
-                        let mut bytes = j.to_bytes()?;
-                        if let Some(s) = &sess {
-                            bytes = s.seal(b"j3.2", &bytes)?;
-                        }
-                        sock.send_to(&bytes, args.sink).await?;
-                    }
-                    Err(e) => eprintln!("bridge: bad telemetry JSON: {e}; payload={text}"),
-                },
-                Err(e) => eprintln!("bridge: non-text payload: {e}"),
+            if let Some(json) = reverse::recv_json(&sock, sess.as_ref(), AAD_J3_2).await? {
+                publisher
+                    .put(json)
+                    .await
+                    .map_err(|e| anyhow::anyhow!("{}", e))?;
+            }
+        }
+    }
+
+    let sock = tokio::net::UdpSocket::bind("0.0.0.0:0").await?;
+
+    #[cfg(feature = "zenoh")]
+    {
+        let selector = args.subscribe.clone();
+        let mut sub = source::Reconnecting::new(
+            move || source::ZenohSource::open(selector.clone()),
+            source::Backoff::new(RECONNECT_INITIAL, RECONNECT_MAX),
+        );
+        if args.once {
+            let timeout = Duration::from_millis(args.once_timeout_ms);
+            let out = run_once(
+                &args,
+                &pipeline,
+                sess.as_ref(),
+                &metrics,
+                &mut sub,
+                &sock,
+                timeout,
+            )
+            .await?;
+            match out {
+                Outbound::Datagram(bytes) => {
+                    info!(bytes = bytes.len(), hex = %hex::encode(&bytes), "sent single frame")
+                }
+                Outbound::Line(line) => info!(%line, "wrote single point"),
             }
+            return Ok(());
         }
+        info!(subscribe = %args.subscribe, sink = %args.sink, "bridge started");
+        return run(
+            &args,
+            &pipeline,
+            sess.as_ref(),
+            &metrics,
+            &mut sub,
+            &sock,
+            shutdown_signal(),
+        )
+        .await;
     }
 
     #[cfg(not(feature = "zenoh"))]
     {
-        println!("bridge compiled without 'zenoh' feature. Rebuild with: cargo run -p bridge --features zenoh -- ...");
+        warn!("bridge compiled without 'zenoh' feature. Rebuild with: cargo run -p bridge --features zenoh -- ...");
+        Ok(())
+    }
+}
+
+/// Resolves on Ctrl-C, or SIGTERM on Unix.
+#[cfg(feature = "zenoh")]
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut term = signal(SignalKind::terminate()).expect("install SIGTERM handler");
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = term.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// Forward samples from `src` until `shutdown` resolves, then send any pending batch and
+/// log a summary.
+async fn run<S: Source>(
+    args: &Args,
+    pipeline: &Pipeline,
+    sess: Option<&e2ee::Session>,
+    metrics: &Metrics,
+    src: &mut S,
+    sock: &tokio::net::UdpSocket,
+    shutdown: impl Future<Output = ()>,
+) -> Result<()> {
+    let mut batcher = (args.batch_window_ms.is_some() || args.batch_max.is_some()).then(|| {
+        let window = args
+            .batch_window_ms
+            .map_or(BATCH_WINDOW, Duration::from_millis);
+        batch::Batcher::new(
+            window,
+            args.batch_max.unwrap_or(BATCH_MAX),
+            args.batch_max_bytes,
+        )
+    });
+    let mut sink = Sink::new(args, sock, metrics)?;
+    tokio::pin!(shutdown);
+    loop {
+        let deadline = batcher.as_ref().and_then(batch::Batcher::deadline);
+        let sample = tokio::select! {
+            biased;
+            () = &mut shutdown => break,
+            sample = src.recv() => sample?,
+            _ = tokio::time::sleep_until(deadline.unwrap_or_else(Instant::now).into()),
+                if deadline.is_some() =>
+            {
+                // Batch window expired with no new sample.
+                if let Some(pdu) = batcher.as_mut().and_then(|b| b.poll(Instant::now())) {
+                    sink.send(&pdu).await?;
+                }
+                continue;
+            }
+        };
+        let bytes = match route(args, pipeline, sess, metrics, &sample)? {
+            None => continue,
+            Some(Outbound::Line(line)) => {
+                if let Err(e) = write_line(args, &line).await {
+                    error!(error = format!("{e:#}"), "influx write failed");
+                }
+                continue;
+            }
+            Some(Outbound::Datagram(bytes)) => bytes,
+        };
+        match batcher.as_mut() {
+            Some(b) => match b.push(&bytes, Instant::now()) {
+                Ok(Some(pdu)) => sink.send(&pdu).await?,
+                Ok(None) => {}
+                Err(e) => {
+                    warn!(bytes = bytes.len(), error = %e, "frame too long to batch; dropping")
+                }
+            },
+            None => {
+                sink.send(&bytes).await?;
+            }
+        }
+    }
+    if let Some(pdu) = batcher.as_mut().and_then(batch::Batcher::flush) {
+        sink.send(&pdu).await?;
+    }
+    info!(
+        received = metrics.received.load(Relaxed),
+        forwarded = metrics.forwarded.load(Relaxed),
+        rate_limited = metrics.rate_limited.load(Relaxed),
+        "bridge shut down"
+    );
+    Ok(())
+}
+
+/// The UDP sink, paced by `--max-rate` and captured by `--record` when set.
+struct Sink<'a> {
+    sock: &'a tokio::net::UdpSocket,
+    addr: SocketAddr,
+    limit: Option<ratelimit::RateLimit>,
+    recorder: Option<record::Recorder>,
+    metrics: &'a Metrics,
+}
+
+impl<'a> Sink<'a> {
+    fn new(args: &Args, sock: &'a tokio::net::UdpSocket, metrics: &'a Metrics) -> Result<Self> {
+        Ok(Self {
+            sock,
+            addr: args.sink,
+            limit: args
+                .max_rate
+                .map(|rate| ratelimit::RateLimit::new(rate, args.over_rate)),
+            recorder: args
+                .record
+                .as_deref()
+                .map(record::Recorder::open)
+                .transpose()?,
+            metrics,
+        })
+    }
+
+    async fn send(&mut self, datagram: &[u8]) -> Result<()> {
+        if let Some(limit) = &mut self.limit {
+            if !limit.admit().await {
+                Metrics::inc(&self.metrics.rate_limited);
+                debug!(bytes = datagram.len(), "over --max-rate; dropping datagram");
+                return Ok(());
+            }
+        }
+        self.sock.send_to(datagram, self.addr).await?;
+        if let Some(recorder) = &mut self.recorder {
+            recorder.write(datagram, std::time::SystemTime::now())?;
+        }
         Ok(())
     }
 }
 
-fn hex_to_session(hex: &str) -> e2ee::Session {
-    let data = hex::decode(hex).expect("invalid hex");
-    e2ee::session_from_psk(&data)
+/// What one sample becomes for `--transport`.
+#[derive(Debug, PartialEq)]
+enum Outbound {
+    /// Bytes for the UDP sink (a single frame, before any batching).
+    Datagram(Vec<u8>),
+    /// An InfluxDB line-protocol point.
+    Line(String),
+}
+
+/// Translate one sample for `--transport`; `None` means it was dropped (the reason is logged).
+fn route(
+    args: &Args,
+    pipeline: &Pipeline,
+    sess: Option<&e2ee::Session>,
+    metrics: &Metrics,
+    sample: &Inbound,
+) -> Result<Option<Outbound>> {
+    if args.transport == Transport::Udp {
+        return Ok(translate(args, pipeline, sess, metrics, sample)?.map(Outbound::Datagram));
+    }
+    let Some((_, j)) = decode(args, pipeline, sess, metrics, sample) else {
+        return Ok(None);
+    };
+    let ts_ns = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_nanos();
+    Ok(Some(Outbound::Line(influx::line(&j, ts_ns))))
+}
+
+async fn write_line(args: &Args, line: &str) -> Result<()> {
+    influx::post(&args.influx_url, args.influx_token.as_deref(), line).await
+}
+
+/// Receive one sample from `src`, translate it and deliver it as `run` would (unbatched).
+/// Fails if nothing arrives within `timeout`, the sample is dropped or the write fails.
+async fn run_once<S: Source>(
+    args: &Args,
+    pipeline: &Pipeline,
+    sess: Option<&e2ee::Session>,
+    metrics: &Metrics,
+    src: &mut S,
+    sock: &tokio::net::UdpSocket,
+    timeout: Duration,
+) -> Result<Outbound> {
+    let sample = tokio::time::timeout(timeout, src.recv())
+        .await
+        .map_err(|_| anyhow::anyhow!("no sample within {} ms", timeout.as_millis()))??;
+    let out = route(args, pipeline, sess, metrics, &sample)?
+        .ok_or_else(|| anyhow::anyhow!("sample was dropped"))?;
+    match &out {
+        Outbound::Datagram(bytes) => Sink::new(args, sock, metrics)?.send(bytes).await?,
+        Outbound::Line(line) => write_line(args, line).await?,
+    }
+    Ok(out)
+}
+
+/// Turn one inbound sample into the bytes to put on the wire; `None` means it was dropped
+/// (the reason is logged).
+fn translate(
+    args: &Args,
+    pipeline: &Pipeline,
+    sess: Option<&e2ee::Session>,
+    metrics: &Metrics,
+    sample: &Inbound,
+) -> Result<Option<Vec<u8>>> {
+    let Some((track, j)) = decode(args, pipeline, sess, metrics, sample) else {
+        return Ok(None);
+    };
+    let mut bytes = j.to_bytes()?;
+    if let Some(s) = sess {
+        let aad = frame_aad(
+            args.aad_from_attachment,
+            &sample.encoding,
+            sample.attachment.as_deref(),
+        );
+        bytes = s
+            .seal(&aad, &bytes)
+            .inspect_err(|_| Metrics::inc(&metrics.seal_failures))?;
+    }
+    Metrics::inc(&metrics.forwarded);
+    debug!(track, sink = %args.sink, bytes = bytes.len(), "forwarding frame");
+    Ok(Some(bytes))
+}
+
+/// Parse a sample's telemetry into a J-Series message (with its track number), logging why
+/// it is dropped if not.
+fn decode(
+    args: &Args,
+    pipeline: &Pipeline,
+    sess: Option<&e2ee::Session>,
+    metrics: &Metrics,
+    sample: &Inbound,
+) -> Option<(u16, JMessage)> {
+    metrics.observe_received(sample.payload.len());
+    let t = match parse_telemetry(&sample.payload, sess) {
+        Ok(t) => t,
+        Err(e) => {
+            Metrics::inc(&metrics.parse_failures);
+            error!(error = format!("{e:#}"), "unreadable telemetry");
+            return None;
+        }
+    };
+    // Transforms drop records deliberately, so that isn't logged.
+    let t = pipeline.apply(t)?;
+    let precision = args
+        .coarse_grid_m
+        .map_or(Precision::Full, |meters| Precision::Grid { meters });
+    match to_message(&t, args.strict_heading, precision) {
+        Ok(j) => Some((t.track, j)),
+        Err(reason) => {
+            warn!(track = t.track, %reason, "dropping track");
+            None
+        }
+    }
+}
+
+/// Telemetry from a JSON payload or, when keyed, from one sealed under [`AAD_TELEMETRY`].
+fn parse_telemetry(payload: &[u8], sess: Option<&e2ee::Session>) -> Result<Telemetry> {
+    let plain = std::str::from_utf8(payload)
+        .context("non-text payload")
+        .and_then(|text| {
+            serde_json::from_str(text)
+                .with_context(|| format!("bad telemetry JSON; payload={text}"))
+        });
+    let (Err(e), Some(s)) = (&plain, sess) else {
+        return plain;
+    };
+    let opened = s.open(AAD_TELEMETRY, payload).map_err(|open| {
+        anyhow::anyhow!("{e:#}, and it does not open as sealed telemetry: {open}")
+    })?;
+    serde_json::from_slice(&opened).context("bad sealed telemetry JSON")
+}
+
+/// Transforms selected on the command line, in a fixed order: allowlist (on the original
+/// track numbers), remap, then offset (the identity transform when none are selected).
+fn pipeline(args: &Args) -> Pipeline {
+    let mut pipeline = Pipeline::default();
+    if !args.only_tracks.is_empty() {
+        pipeline.push(transform::TrackAllowlist {
+            tracks: args.only_tracks.iter().copied().collect(),
+        });
+    }
+    if !args.remap_track.is_empty() {
+        pipeline.push(transform::TrackRemap {
+            map: args.remap_track.clone(),
+        });
+    }
+    if args.offset_lat.is_some() || args.offset_lon.is_some() {
+        pipeline.push(transform::CoordinateOffset {
+            lat_deg: args.offset_lat.unwrap_or(0.0),
+            lon_deg: args.offset_lon.unwrap_or(0.0),
+        });
+    }
+    if pipeline.is_empty() {
+        pipeline.push(transform::Passthrough);
+    }
+    pipeline
+}
+
+/// Build the J-Series message for a telemetry record, or explain why it must be dropped.
+fn to_message(
+    t: &Telemetry,
+    strict_heading: bool,
+    precision: Precision,
+) -> Result<JMessage, String> {
+    let heading_deg = check_heading(t.heading_deg, strict_heading)
+        .ok_or_else(|| format!("heading {} outside [0, 360)", t.heading_deg))?;
+    let heading_cdeg = jseries::units::deg_to_cdeg(heading_deg);
+    let track = J3_2AirTrack::from_geo_with_precision(
+        t.track,
+        t.lat,
+        t.lon,
+        t.alt_m,
+        t.speed_ms,
+        heading_cdeg,
+        precision,
+    );
+    match t.identity_code.map(Identity::from_code) {
+        None => Ok(JMessage::J3_2(track)),
+        Some(Some(identity)) => Ok(JMessage::J3_2V2(J3_2AirTrackV2::new(track, identity))),
+        Some(None) => Err(format!("invalid identity code {:?}", t.identity_code)),
+    }
+}
+
+/// Bring a heading into `[0, 360)`: wrap it, or reject it when `strict`. Non-finite
+/// headings are always rejected.
+fn check_heading(heading_deg: f64, strict: bool) -> Option<f64> {
+    match heading_deg {
+        h if (0.0..360.0).contains(&h) => Some(h),
+        h if strict || !h.is_finite() => None,
+        h => Some(h.rem_euclid(360.0)),
+    }
+}
+
+/// AAD for an outbound frame, optionally bound to the sample's transport metadata.
+/// An absent attachment is encoded differently from an empty one.
+fn frame_aad(bind_transport: bool, encoding: &str, attachment: Option<&[u8]>) -> Vec<u8> {
+    if !bind_transport {
+        return AAD_J3_2.to_vec();
+    }
+    match attachment {
+        Some(att) => e2ee::bind_aad(&[AAD_J3_2, encoding.as_bytes(), att]),
+        None => e2ee::bind_aad(&[AAD_J3_2, encoding.as_bytes()]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn heading_450_wraps_when_lenient() {
+        assert_eq!(check_heading(450.0, false), Some(90.0));
+        assert_eq!(check_heading(359.5, false), Some(359.5));
+        assert_eq!(check_heading(-90.0, false), Some(270.0));
+        assert_eq!(check_heading(f64::NAN, false), None);
+    }
+
+    #[test]
+    fn heading_450_rejected_when_strict() {
+        assert_eq!(check_heading(450.0, true), None);
+        assert_eq!(check_heading(360.0, true), None);
+        assert_eq!(check_heading(-0.5, true), None);
+        assert_eq!(check_heading(359.0, true), Some(359.0));
+    }
+
+    #[test]
+    fn attachment_bound_aad_rejects_other_attachment() {
+        let sess = e2ee::session_from_psk(b"bridge");
+        let aad = frame_aad(true, "application/json", Some(b"uav1"));
+        let frame = sess.seal(&aad, b"j3.2 bytes").unwrap();
+        assert_eq!(sess.open(&aad, &frame).unwrap(), b"j3.2 bytes");
+
+        let other = frame_aad(true, "application/json", Some(b"uav2"));
+        assert!(sess.open(&other, &frame).is_err());
+        assert!(sess
+            .open(&frame_aad(true, "application/json", None), &frame)
+            .is_err());
+        assert!(sess.open(AAD_J3_2, &frame).is_err());
+        assert_eq!(
+            frame_aad(false, "application/json", Some(b"uav1")),
+            AAD_J3_2
+        );
+    }
+
+    /// Scripted source: yields queued samples, then never resolves.
+    struct MockSource(std::collections::VecDeque<Inbound>);
+
+    impl Source for MockSource {
+        async fn recv(&mut self) -> Result<Inbound> {
+            match self.0.pop_front() {
+                Some(s) => Ok(s),
+                None => std::future::pending().await,
+            }
+        }
+    }
+
+    const TELEMETRY: &str =
+        r#"{"track":42,"lat":45.0,"lon":-122.0,"alt_m":1500.0,"speed_ms":220,"heading_deg":271}"#;
+
+    #[tokio::test]
+    async fn once_sends_single_translated_sample() {
+        let sink = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let sink_addr = sink.local_addr().unwrap().to_string();
+        let args = Args::parse_from(["bridge", "--once", "--sink", &sink_addr]);
+        let sock = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let mut src = MockSource([Inbound::json(TELEMETRY), Inbound::json(TELEMETRY)].into());
+
+        let Outbound::Datagram(sent) = run_once(
+            &args,
+            &Pipeline::default(),
+            None,
+            &Metrics::default(),
+            &mut src,
+            &sock,
+            Duration::from_secs(1),
+        )
+        .await
+        .unwrap() else {
+            panic!("expected a datagram");
+        };
+        let mut buf = [0u8; 64];
+        let n = sink.recv(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], sent);
+        let JMessage::J3_2(track) = JMessage::from_bytes(&sent).unwrap() else {
+            panic!("expected J3.2");
+        };
+        assert_eq!(track.track, 42);
+        assert_eq!(track.heading_cdeg, 27_100);
+        // The publisher's `--direct-udp` test pins the same bytes for this telemetry.
+        assert_eq!(hex::encode(&sent), "32002abfffe527d00a80c50006e34ee0");
+        assert_eq!(src.0.len(), 1, "only one sample consumed");
+    }
+
+    #[tokio::test]
+    async fn sealed_telemetry_is_opened_and_forwarded() {
+        let sink = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let sink_addr = sink.local_addr().unwrap().to_string();
+        let args = Args::parse_from(["bridge", "--once", "--sink", &sink_addr]);
+        let sock = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let sess = e2ee::session_from_psk(b"bridge");
+        let sealed = Inbound {
+            payload: sess.seal(AAD_TELEMETRY, TELEMETRY.as_bytes()).unwrap(),
+            encoding: "application/octet-stream".to_string(),
+            attachment: None,
+        };
+        let mut src = MockSource([sealed].into());
+
+        let Outbound::Datagram(sent) = run_once(
+            &args,
+            &Pipeline::default(),
+            Some(&sess),
+            &Metrics::default(),
+            &mut src,
+            &sock,
+            Duration::from_secs(1),
+        )
+        .await
+        .unwrap() else {
+            panic!("expected a datagram");
+        };
+        let plain = sess.open(AAD_J3_2, &sent).unwrap();
+        let JMessage::J3_2(track) = JMessage::from_bytes(&plain).unwrap() else {
+            panic!("expected J3.2");
+        };
+        assert_eq!(track.track, 42);
+
+        let other = e2ee::session_from_psk(b"other");
+        let err = parse_telemetry(&sess.seal(AAD_TELEMETRY, b"{}").unwrap(), Some(&other));
+        assert!(format!("{:#}", err.unwrap_err()).contains("does not open as sealed telemetry"));
+        assert!(parse_telemetry(TELEMETRY.as_bytes(), Some(&other)).is_ok());
+    }
+
+    /// Formatted log output shared with the subscriber under test.
+    #[derive(Clone, Default)]
+    struct Captured(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn logs_debug_event_per_forwarded_message() {
+        let logs = Captured::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::DEBUG)
+            .with_ansi(false)
+            .with_writer(move || writer.clone())
+            .finish();
+        let args = Args::parse_from(["bridge"]);
+        let pipeline = Pipeline::default();
+        let metrics = Metrics::default();
+        tracing::subscriber::with_default(subscriber, || {
+            for _ in 0..3 {
+                let sent = translate(&args, &pipeline, None, &metrics, &Inbound::json(TELEMETRY));
+                assert!(sent.unwrap().is_some());
+            }
+            let dropped = translate(&args, &pipeline, None, &metrics, &Inbound::json("not json"));
+            assert!(dropped.unwrap().is_none());
+        });
+
+        let out = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        let forwarded: Vec<&str> = out
+            .lines()
+            .filter(|l| l.contains("forwarding frame"))
+            .collect();
+        assert_eq!(forwarded.len(), 3, "{out}");
+        for field in ["DEBUG", "track=42", "sink=127.0.0.1:5000", "bytes=16"] {
+            assert!(forwarded[0].contains(field), "{field}: {}", forwarded[0]);
+        }
+        assert!(out
+            .lines()
+            .any(|l| l.contains("ERROR") && l.contains("unreadable telemetry")));
+    }
+
+    #[tokio::test]
+    async fn metrics_endpoint_counts_forwarded_messages() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let metrics = std::sync::Arc::new(Metrics::default());
+        tokio::spawn(metrics::serve(listener, metrics.clone()));
+
+        let args = Args::parse_from(["bridge"]);
+        for _ in 0..5 {
+            let sample = Inbound::json(TELEMETRY);
+            let sent = translate(&args, &Pipeline::default(), None, &metrics, &sample).unwrap();
+            assert!(sent.is_some());
+        }
+        let bad = Inbound::json("{");
+        assert!(translate(&args, &Pipeline::default(), None, &metrics, &bad)
+            .unwrap()
+            .is_none());
+
+        let response = metrics::scrape(addr).await;
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{response}");
+        for line in [
+            "bridge_messages_received_total 6",
+            "bridge_messages_forwarded_total 5",
+            "bridge_parse_failures_total 1",
+            "bridge_seal_failures_total 0",
+            "bridge_payload_bytes_count 6",
+        ] {
+            assert!(response.lines().any(|l| l == line), "{line}: {response}");
+        }
+    }
+
+    /// A `run` loop on its own task, fed `inputs` and sending to `sink`.
+    struct Running {
+        sink: tokio::net::UdpSocket,
+        metrics: std::sync::Arc<Metrics>,
+        stop: tokio::sync::oneshot::Sender<()>,
+        task: tokio::task::JoinHandle<Result<()>>,
+    }
+
+    /// Start `run` with `--sink` pointed at a fresh socket, plus `extra_args`.
+    async fn spawn_run(extra_args: &[&str], inputs: impl IntoIterator<Item = Inbound>) -> Running {
+        let sink = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let sink_addr = sink.local_addr().unwrap().to_string();
+        let args = Args::parse_from(["bridge", "--sink", &sink_addr].iter().chain(extra_args));
+        let sock = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let metrics = std::sync::Arc::new(Metrics::default());
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let mut src = MockSource(inputs.into_iter().collect());
+        let task = tokio::spawn({
+            let metrics = metrics.clone();
+            async move {
+                let shutdown = async {
+                    let _ = stopped.await;
+                };
+                let pipeline = pipeline(&args);
+                run(&args, &pipeline, None, &metrics, &mut src, &sock, shutdown).await
+            }
+        });
+        Running {
+            sink,
+            metrics,
+            stop,
+            task,
+        }
+    }
+
+    impl Running {
+        /// Trigger shutdown, check the loop returns cleanly within a second, and hand back
+        /// the sink.
+        async fn stop(self) -> tokio::net::UdpSocket {
+            self.stop.send(()).unwrap();
+            tokio::time::timeout(Duration::from_secs(1), self.task)
+                .await
+                .expect("loop exits on shutdown")
+                .unwrap()
+                .unwrap();
+            self.sink
+        }
+    }
+
+    #[tokio::test]
+    async fn shutdown_trigger_stops_loop_and_flushes_batch() {
+        let inputs = [Inbound::json(TELEMETRY), Inbound::json(TELEMETRY)];
+        let running = spawn_run(&["--batch-window-ms", "60000"], inputs).await;
+        while running.metrics.forwarded.load(Relaxed) < 2 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        let sink = running.stop().await;
+
+        // The partly filled batch went out on the way down.
+        let mut buf = [0u8; 256];
+        let n = sink.recv(&mut buf).await.unwrap();
+        assert_eq!(jseries::split_frames(&buf[..n]).unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn batch_of_three_goes_out_as_one_datagram() {
+        let inputs = std::iter::repeat_with(|| Inbound::json(TELEMETRY)).take(3);
+        let running = spawn_run(&["--batch", "3", "--batch-ms", "1000"], inputs).await;
+
+        // The third frame fills the batch well inside the 1 s window.
+        let mut buf = [0u8; 256];
+        let n = tokio::time::timeout(Duration::from_millis(500), running.sink.recv(&mut buf))
+            .await
+            .expect("full batch sent before the window closes")
+            .unwrap();
+        let frames = jseries::split_frames(&buf[..n]).unwrap();
+        assert_eq!(frames.len(), 3);
+        for frame in frames {
+            let JMessage::J3_2(t) = JMessage::from_bytes(frame).unwrap() else {
+                panic!("expected J3.2");
+            };
+            assert_eq!(t.track, 42);
+        }
+
+        let sink = running.stop().await;
+        assert!(
+            tokio::time::timeout(Duration::from_millis(50), sink.recv(&mut buf))
+                .await
+                .is_err(),
+            "nothing left to flush"
+        );
+    }
+
+    #[tokio::test]
+    async fn recorded_frames_replay_byte_for_byte() {
+        let path = std::env::temp_dir().join(format!("bridge-replay-{}.bin", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let path_arg = path.to_str().unwrap();
+        let other = r#"{"track":7,"lat":1.0,"lon":2.0,"alt_m":3.0,"speed_ms":4,"heading_deg":5}"#;
+        let inputs = [Inbound::json(TELEMETRY), Inbound::json(other)];
+        let running = spawn_run(&["--record", path_arg], inputs).await;
+        let mut sent = Vec::new();
+        let mut buf = [0u8; 256];
+        for _ in 0..2 {
+            let n = running.sink.recv(&mut buf).await.unwrap();
+            sent.push(buf[..n].to_vec());
+        }
+        let sink = running.stop().await;
+
+        let sink_addr = sink.local_addr().unwrap().to_string();
+        let args = Args::parse_from(["bridge", "--sink", &sink_addr, "--replay", path_arg]);
+        let sock = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let replay = args.replay.as_deref().unwrap();
+        assert_eq!(record::replay(replay, &sock, args.sink).await.unwrap(), 2);
+        for expected in &sent {
+            let n = sink.recv(&mut buf).await.unwrap();
+            assert_eq!(&buf[..n], expected.as_slice());
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn only_tracks_forwards_listed_tracks() {
+        let args = Args::parse_from(["bridge", "--only-tracks", "42"]);
+        let only = pipeline(&args);
+        let metrics = Metrics::default();
+        let sample = |track: u16| Inbound::json(&TELEMETRY.replace("42", &track.to_string()));
+        let sent = translate(&args, &only, None, &metrics, &sample(42)).unwrap();
+        assert!(sent.is_some());
+        let dropped = translate(&args, &only, None, &metrics, &sample(7)).unwrap();
+        assert_eq!(dropped, None);
+        assert_eq!(metrics.forwarded.load(Relaxed), 1);
+
+        let all = Args::parse_from(["bridge"]);
+        let sent = translate(&all, &pipeline(&all), None, &metrics, &sample(7)).unwrap();
+        assert!(sent.is_some());
+    }
+
+    #[test]
+    fn max_rate_must_be_finite_and_positive() {
+        for bad in ["0", "-5", "inf"] {
+            let arg = format!("--max-rate={bad}");
+            assert!(Args::try_parse_from(["bridge", &arg]).is_err(), "{bad}");
+        }
+        let args = Args::parse_from(["bridge", "--max-rate", "0.5"]);
+        assert_eq!(args.max_rate, Some(0.5));
+    }
+
+    #[tokio::test]
+    async fn max_rate_drops_burst_beyond_budget() {
+        let burst = std::iter::repeat_with(|| Inbound::json(TELEMETRY)).take(100);
+        let running = spawn_run(&["--max-rate", "10"], burst).await;
+        let metrics = running.metrics.clone();
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while metrics.received.load(Relaxed) < 100 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("burst handled within a second");
+        let sink = running.stop().await;
+
+        let mut sent = 0;
+        let mut buf = [0u8; 64];
+        while tokio::time::timeout(Duration::from_millis(50), sink.recv(&mut buf))
+            .await
+            .is_ok()
+        {
+            sent += 1;
+        }
+        assert!((10..=11).contains(&sent), "sent {sent}");
+        assert_eq!(metrics.rate_limited.load(Relaxed), 100 - sent);
+    }
+
+    #[tokio::test]
+    async fn sealed_publisher_telemetry_keeps_fractional_heading() {
+        let sink = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let sink_addr = sink.local_addr().unwrap().to_string();
+        let args = Args::parse_from(["bridge", "--once", "--sink", &sink_addr]);
+        let sock = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let sess = e2ee::session_from_psk(b"bridge");
+        // `serde_json::to_string` of the publisher's `TelemetryOut` with its defaults: an
+        // integer altitude and an `f32` heading.
+        let json = r#"{"track":42,"lat":45.1234567,"lon":-122.9876543,"alt_m":1500,"speed_ms":220,"heading_deg":271.5}"#;
+        let sealed = Inbound {
+            payload: sess.seal(AAD_TELEMETRY, json.as_bytes()).unwrap(),
+            encoding: "application/octet-stream".to_string(),
+            attachment: None,
+        };
+        let mut src = MockSource([sealed].into());
+
+        let out = run_once(
+            &args,
+            &Pipeline::default(),
+            Some(&sess),
+            &Metrics::default(),
+            &mut src,
+            &sock,
+            Duration::from_secs(1),
+        )
+        .await
+        .unwrap();
+        let Outbound::Datagram(sent) = out else {
+            panic!("expected a datagram, got {out:?}");
+        };
+        let plain = sess.open(AAD_J3_2, &sent).unwrap();
+        let JMessage::J3_2(track) = JMessage::from_bytes(&plain).unwrap() else {
+            panic!("expected J3.2");
+        };
+        assert_eq!((track.track, track.heading_cdeg), (42, 27_150));
+    }
+
+    #[tokio::test]
+    async fn once_times_out_without_sample() {
+        let args = Args::parse_from(["bridge", "--once"]);
+        let sock = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let mut src = MockSource(Default::default());
+        let err = run_once(
+            &args,
+            &Pipeline::default(),
+            None,
+            &Metrics::default(),
+            &mut src,
+            &sock,
+            Duration::from_millis(20),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("no sample within 20 ms"));
+    }
+
+    #[tokio::test]
+    async fn once_writes_to_influx_not_udp() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let influx = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/api/v2/write", influx.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut conn, _) = influx.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 512];
+            // Read until the body the client announced has arrived.
+            let body = loop {
+                let n = conn.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request).into_owned();
+                if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                    let len: usize = head
+                        .lines()
+                        .find_map(|l| l.strip_prefix("Content-Length: "))
+                        .unwrap()
+                        .parse()
+                        .unwrap();
+                    if body.len() >= len {
+                        break body.to_string();
+                    }
+                }
+            };
+            conn.write_all(b"HTTP/1.1 204 No Content\r\n\r\n")
+                .await
+                .unwrap();
+            body
+        });
+        let sink = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let sink_addr = sink.local_addr().unwrap().to_string();
+        let args = Args::parse_from([
+            "bridge",
+            "--once",
+            "--sink",
+            &sink_addr,
+            "--transport",
+            "influx",
+            "--influx-url",
+            &url,
+        ]);
+        let sock = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let mut src = MockSource([Inbound::json(TELEMETRY)].into());
+
+        let out = run_once(
+            &args,
+            &Pipeline::default(),
+            None,
+            &Metrics::default(),
+            &mut src,
+            &sock,
+            Duration::from_secs(1),
+        )
+        .await
+        .unwrap();
+        let Outbound::Line(line) = out else {
+            panic!("expected an influx point, got {out:?}");
+        };
+        assert_eq!(server.await.unwrap(), line);
+        assert!(line.starts_with("airtrack,track=42 "), "{line}");
+        let mut buf = [0u8; 64];
+        assert!(
+            tokio::time::timeout(Duration::from_millis(50), sink.recv(&mut buf))
+                .await
+                .is_err(),
+            "nothing sent over UDP"
+        );
+    }
 }
@@ -0,0 +1,160 @@
+//! Prometheus counters for the forwarding loop, served as text exposition over plain
+//! HTTP/1.1 on `GET /metrics`.
+
+use anyhow::Result;
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering::Relaxed};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::warn;
+
+/// Upper bounds (bytes) of the payload size histogram buckets; `+Inf` is implied.
+const SIZE_BUCKETS: [u64; 7] = [64, 128, 256, 512, 1024, 2048, 4096];
+
+#[derive(Debug, Default)]
+pub struct Metrics {
+    pub received: AtomicU64,
+    pub forwarded: AtomicU64,
+    pub seal_failures: AtomicU64,
+    pub parse_failures: AtomicU64,
+    pub rate_limited: AtomicU64,
+    /// Per-bucket (non-cumulative) counts; the last slot is `+Inf`.
+    size_buckets: [AtomicU64; SIZE_BUCKETS.len() + 1],
+    size_sum: AtomicU64,
+}
+
+impl Metrics {
+    pub fn inc(counter: &AtomicU64) {
+        counter.fetch_add(1, Relaxed);
+    }
+
+    /// Count an inbound sample and record its payload size.
+    pub fn observe_received(&self, payload_len: usize) {
+        Self::inc(&self.received);
+        let len = payload_len as u64;
+        let bucket = SIZE_BUCKETS
+            .iter()
+            .position(|&le| len <= le)
+            .unwrap_or(SIZE_BUCKETS.len());
+        self.size_buckets[bucket].fetch_add(1, Relaxed);
+        self.size_sum.fetch_add(len, Relaxed);
+    }
+
+    /// Text exposition format (version 0.0.4).
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for (name, help, counter) in [
+            (
+                "bridge_messages_received_total",
+                "Inbound samples seen.",
+                &self.received,
+            ),
+            (
+                "bridge_messages_forwarded_total",
+                "Frames handed to the sink.",
+                &self.forwarded,
+            ),
+            (
+                "bridge_seal_failures_total",
+                "Frames that failed to seal.",
+                &self.seal_failures,
+            ),
+            (
+                "bridge_parse_failures_total",
+                "Samples with unreadable telemetry.",
+                &self.parse_failures,
+            ),
+            (
+                "bridge_rate_limited_total",
+                "Datagrams dropped by --max-rate.",
+                &self.rate_limited,
+            ),
+        ] {
+            let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} counter");
+            let _ = writeln!(out, "{name} {}", counter.load(Relaxed));
+        }
+        let name = "bridge_payload_bytes";
+        let _ = writeln!(
+            out,
+            "# HELP {name} Inbound payload sizes.\n# TYPE {name} histogram"
+        );
+        let mut cumulative = 0;
+        for (i, count) in self.size_buckets.iter().enumerate() {
+            cumulative += count.load(Relaxed);
+            let le = SIZE_BUCKETS
+                .get(i)
+                .map_or("+Inf".to_string(), u64::to_string);
+            let _ = writeln!(out, "{name}_bucket{{le=\"{le}\"}} {cumulative}");
+        }
+        let _ = writeln!(out, "{name}_sum {}", self.size_sum.load(Relaxed));
+        let _ = writeln!(out, "{name}_count {cumulative}");
+        out
+    }
+}
+
+/// Answer scrapes on `listener` until the task is dropped.
+pub async fn serve(listener: TcpListener, metrics: Arc<Metrics>) -> Result<()> {
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let metrics = metrics.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle(stream, &metrics).await {
+                warn!(%peer, error = %e, "metrics request failed");
+            }
+        });
+    }
+}
+
+async fn handle(mut stream: TcpStream, metrics: &Metrics) -> Result<()> {
+    // A scrape request fits in one read; only the request line matters.
+    let mut buf = [0u8; 1024];
+    let n = stream.read(&mut buf).await?;
+    let request = String::from_utf8_lossy(&buf[..n]);
+    let (status, body) = match request.split_whitespace().take(2).collect::<Vec<_>>()[..] {
+        ["GET", "/metrics"] => ("200 OK", metrics.render()),
+        _ => ("404 Not Found", String::new()),
+    };
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain; version=0.0.4\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+pub async fn scrape(addr: std::net::SocketAddr) -> String {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream
+        .write_all(b"GET /metrics HTTP/1.1\r\nHost: test\r\n\r\n")
+        .await
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram_buckets_are_cumulative() {
+        let m = Metrics::default();
+        for len in [10, 100, 5000] {
+            m.observe_received(len);
+        }
+        let out = m.render();
+        assert!(
+            out.contains("bridge_payload_bytes_bucket{le=\"64\"} 1\n"),
+            "{out}"
+        );
+        assert!(out.contains("bridge_payload_bytes_bucket{le=\"128\"} 2\n"));
+        assert!(out.contains("bridge_payload_bytes_bucket{le=\"4096\"} 2\n"));
+        assert!(out.contains("bridge_payload_bytes_bucket{le=\"+Inf\"} 3\n"));
+        assert!(out.contains("bridge_payload_bytes_sum 5110\n"));
+        assert!(out.contains("bridge_messages_received_total 3\n"));
+    }
+}
@@ -0,0 +1,134 @@
+//! Token-bucket pacing of outbound datagrams.
+
+use std::time::{Duration, Instant};
+
+/// Slack for float drift when refills add up to exactly one token.
+const EPSILON: f64 = 1e-9;
+
+/// What to do with a datagram once the budget is spent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OverRate {
+    /// Discard it (counted in the metrics).
+    Drop,
+    /// Hold the loop until a token frees up, so samples wait upstream.
+    Queue,
+}
+
+/// `rate` tokens per second, holding at most one second's worth; the caller supplies the
+/// clock so it can be driven in tests.
+#[derive(Debug)]
+pub struct TokenBucket {
+    rate: f64,
+    capacity: f64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl TokenBucket {
+    /// A full bucket at `now`.
+    pub fn new(rate: f64, now: Instant) -> Self {
+        let capacity = rate.max(1.0);
+        Self {
+            rate,
+            capacity,
+            tokens: capacity,
+            last: now,
+        }
+    }
+
+    /// Take a token if one is available at `now`.
+    pub fn try_take(&mut self, now: Instant) -> bool {
+        self.refill(now);
+        if self.tokens + EPSILON < 1.0 {
+            return false;
+        }
+        self.tokens = (self.tokens - 1.0).max(0.0);
+        true
+    }
+
+    /// When the next token is available (`now` if one already is).
+    pub fn ready_at(&mut self, now: Instant) -> Instant {
+        self.refill(now);
+        let missing = (1.0 - self.tokens).max(0.0);
+        now + Duration::from_secs_f64(missing / self.rate)
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.last = now;
+    }
+}
+
+/// Parse a `--max-rate` value: a finite, positive number of datagrams per second.
+pub fn parse_rate(s: &str) -> Result<f64, String> {
+    let rate: f64 = s.trim().parse().map_err(|e| format!("{s:?}: {e}"))?;
+    if !(rate.is_finite() && rate > 0.0) {
+        return Err(format!("{s:?}: must be a finite rate above 0"));
+    }
+    Ok(rate)
+}
+
+/// `--max-rate` with its `--over-rate` policy.
+#[derive(Debug)]
+pub struct RateLimit {
+    bucket: TokenBucket,
+    mode: OverRate,
+}
+
+impl RateLimit {
+    pub fn new(per_sec: f64, mode: OverRate) -> Self {
+        Self {
+            bucket: TokenBucket::new(per_sec, Instant::now()),
+            mode,
+        }
+    }
+
+    /// Whether the next datagram may go out; in `Queue` mode this waits for a token.
+    pub async fn admit(&mut self) -> bool {
+        match self.mode {
+            OverRate::Drop => self.bucket.try_take(Instant::now()),
+            OverRate::Queue => {
+                let ready = self.bucket.ready_at(Instant::now());
+                tokio::time::sleep_until(ready.into()).await;
+                self.bucket.try_take(ready.max(Instant::now()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rate_rejects_non_positive_and_non_finite() {
+        assert_eq!(parse_rate("2.5"), Ok(2.5));
+        for bad in ["0", "-1", "inf", "NaN", "fast"] {
+            assert!(parse_rate(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn burst_is_capped_at_one_second_of_budget() {
+        let t0 = Instant::now();
+        let mut bucket = TokenBucket::new(10.0, t0);
+        let sent = (0..100).filter(|_| bucket.try_take(t0)).count();
+        assert_eq!(sent, 10);
+        assert_eq!(bucket.ready_at(t0), t0 + Duration::from_millis(100));
+        assert!(!bucket.try_take(t0 + Duration::from_millis(99)));
+        assert!(bucket.try_take(t0 + Duration::from_millis(200)));
+    }
+
+    #[test]
+    fn paced_inputs_pass_at_the_rate() {
+        let t0 = Instant::now();
+        let mut bucket = TokenBucket::new(10.0, t0);
+        // Drain the initial burst, then offer 100 inputs spread over the next second.
+        while bucket.try_take(t0) {}
+        let sent = (1..=100)
+            .filter(|i| bucket.try_take(t0 + Duration::from_millis(10 * i)))
+            .count();
+        assert_eq!(sent, 10);
+    }
+}
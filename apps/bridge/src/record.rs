@@ -0,0 +1,109 @@
+//! Capture of exactly what the bridge put on the wire, for offline reproduction.
+//!
+//! A record file is a concatenation of `[u64 big-endian µs since the Unix epoch]` followed by
+//! a `jseries::push_frame` record (`[u16 big-endian length][datagram bytes]`), one per
+//! datagram sent. `replay` re-emits them with the original gaps between sends.
+
+use anyhow::{ensure, Context, Result};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::info;
+
+/// Appends outbound datagrams to a record file.
+pub struct Recorder {
+    file: File,
+}
+
+impl Recorder {
+    /// Open `path` for appending, creating it if needed.
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("open record file {}", path.display()))?;
+        Ok(Self { file })
+    }
+
+    /// Append one datagram sent at `at`.
+    pub fn write(&mut self, datagram: &[u8], at: SystemTime) -> Result<()> {
+        let micros = u64::try_from(at.duration_since(UNIX_EPOCH)?.as_micros())?;
+        let mut record = micros.to_be_bytes().to_vec();
+        jseries::push_frame(&mut record, datagram)?;
+        // One write per record so a crash can't interleave a partial prefix.
+        self.file.write_all(&record)?;
+        Ok(())
+    }
+}
+
+/// Parse a record file into `(µs since the Unix epoch, datagram)` pairs, in file order.
+pub fn parse(mut input: &[u8]) -> Result<Vec<(u64, Vec<u8>)>> {
+    let mut out = Vec::new();
+    while !input.is_empty() {
+        let offset = out.len();
+        ensure!(input.len() >= 10, "record {offset}: truncated header");
+        let micros = u64::from_be_bytes(input[..8].try_into()?);
+        let len = usize::from(u16::from_be_bytes([input[8], input[9]]));
+        let datagram = input
+            .get(10..10 + len)
+            .with_context(|| format!("record {offset}: truncated datagram"))?;
+        out.push((micros, datagram.to_vec()));
+        input = &input[10 + len..];
+    }
+    Ok(out)
+}
+
+/// Send every datagram recorded at `path` to `sink`, sleeping out the recorded gap before
+/// each one. Returns the number sent.
+pub async fn replay(path: &Path, sock: &tokio::net::UdpSocket, sink: SocketAddr) -> Result<usize> {
+    let bytes = std::fs::read(path).with_context(|| format!("read {}", path.display()))?;
+    let records = parse(&bytes)?;
+    let mut prev = None;
+    for (micros, datagram) in &records {
+        if let Some(prev) = prev {
+            // Clock steps backwards in the capture replay immediately.
+            tokio::time::sleep(Duration::from_micros(micros.saturating_sub(prev))).await;
+        }
+        prev = Some(*micros);
+        sock.send_to(datagram, sink).await?;
+    }
+    info!(
+        datagrams = records.len(),
+        path = %path.display(),
+        %sink,
+        "record replay finished"
+    );
+    Ok(records.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rejects_truncated_records() {
+        let path = std::env::temp_dir().join(format!("bridge-record-{}.bin", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut recorder = Recorder::open(&path).unwrap();
+        let t0 = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        recorder.write(b"abc", t0).unwrap();
+        recorder.write(b"", t0 + Duration::from_millis(5)).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            parse(&bytes).unwrap(),
+            vec![
+                (1_700_000_000_000_000, b"abc".to_vec()),
+                (1_700_000_000_005_000, Vec::new()),
+            ]
+        );
+        let err = parse(&bytes[..12]).unwrap_err();
+        assert_eq!(err.to_string(), "record 0: truncated datagram");
+        let err = parse(&bytes[..17]).unwrap_err();
+        assert_eq!(err.to_string(), "record 1: truncated header");
+    }
+}
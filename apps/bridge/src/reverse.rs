@@ -0,0 +1,135 @@
+//! Reverse direction: J-Series frames off UDP, back to telemetry JSON for Zenoh.
+
+use crate::Telemetry;
+use anyhow::{bail, Result};
+use jseries::{J3_2AirTrack, JMessage};
+use tokio::net::UdpSocket;
+use tracing::warn;
+
+/// Open (if keyed) and decode one frame.
+pub fn decode_frame(frame: &[u8], sess: Option<&e2ee::Session>, aad: &[u8]) -> Result<JMessage> {
+    let plain = match sess {
+        Some(s) => s.open(aad, frame)?,
+        None => frame.to_vec(),
+    };
+    Ok(JMessage::from_bytes(&plain)?)
+}
+
+/// Telemetry for a decoded air track, in the shape the forward path accepts. Heading comes
+/// back in degrees, to the field's centidegree resolution; other kinds have no telemetry
+/// equivalent.
+/// Tracks with out-of-range fields (see `J3_2AirTrack::validate`) are rejected.
+pub fn to_telemetry(msg: &JMessage) -> Result<Telemetry> {
+    let (t, identity_code) = match msg {
+        JMessage::J3_2(t) => (t, None),
+        JMessage::J3_2V2(v) => (&v.base, Some(v.identity_code)),
+        JMessage::J3_2V3(v) => (&v.base.base, Some(v.base.identity_code)),
+        other => bail!("no telemetry for message id {:#04x}", other.id()),
+    };
+    t.validate()?;
+    Ok(telemetry(t, identity_code))
+}
+
+fn telemetry(t: &J3_2AirTrack, identity_code: Option<u8>) -> Telemetry {
+    Telemetry {
+        track: t.track,
+        lat: t.lat_deg(),
+        lon: t.lon_deg(),
+        alt_m: t.alt_m(),
+        speed_ms: t.speed_ms,
+        heading_deg: f64::from(t.heading_cdeg) / 100.0,
+        identity_code,
+    }
+}
+
+/// Receive one datagram and turn it into telemetry JSON; `None` means it was dropped
+/// (the reason is logged).
+pub async fn recv_json(
+    sock: &UdpSocket,
+    sess: Option<&e2ee::Session>,
+    aad: &[u8],
+) -> Result<Option<String>> {
+    let mut buf = [0u8; 2048];
+    let (n, from) = sock.recv_from(&mut buf).await?;
+    match decode_frame(&buf[..n], sess, aad).and_then(|msg| to_telemetry(&msg)) {
+        Ok(t) => Ok(Some(serde_json::to_string(&t)?)),
+        Err(e) => {
+            warn!(bytes = n, %from, error = %e, "dropping inbound frame");
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jseries::{Identity, Precision};
+
+    #[tokio::test]
+    async fn forwarded_telemetry_comes_back_unchanged() {
+        let rx = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let tx = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let sess = e2ee::session_from_psk(b"reverse");
+        let json = r#"{"track":42,"lat":45.0,"lon":-122.0,"alt_m":1500.0,"speed_ms":220,"heading_deg":271.5,"identity_code":2}"#;
+        let sent: Telemetry = serde_json::from_str(json).unwrap();
+        let v2 = crate::to_message(&sent, false, Precision::Full).unwrap();
+        assert!(matches!(v2, JMessage::J3_2V2(_)));
+        let sealed = sess.seal(b"j3.2", &v2.to_bytes().unwrap()).unwrap();
+        tx.send_to(&sealed, rx.local_addr().unwrap()).await.unwrap();
+
+        let json = recv_json(&rx, Some(&sess), b"j3.2").await.unwrap().unwrap();
+        let back: Telemetry = serde_json::from_str(&json).unwrap();
+        // Position comes back quantized to the 19-bit fields, altitude to 25 ft steps.
+        for (got, want, tol) in [
+            (back.lat, sent.lat, 1e-3),
+            (back.lon, sent.lon, 1e-3),
+            (back.alt_m, sent.alt_m, 7.62),
+        ] {
+            assert!((got - want).abs() < tol, "{json}");
+        }
+        assert_eq!(
+            (
+                back.track,
+                back.speed_ms,
+                back.heading_deg,
+                back.identity_code
+            ),
+            (42, 220, 271.5, Some(Identity::Friend.code())),
+            "{json}"
+        );
+
+        // Wrong key: logged and dropped, not an error.
+        tx.send_to(&sealed, rx.local_addr().unwrap()).await.unwrap();
+        let other = e2ee::session_from_psk(b"other");
+        assert_eq!(recv_json(&rx, Some(&other), b"j3.2").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn out_of_range_heading_is_dropped() {
+        let rx = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let tx = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let mut track = J3_2AirTrack::from_geo(42, 45.0, -122.0, 1500.0, 220, 0);
+        track.heading_cdeg = 0xFFFF;
+        let frame = JMessage::J3_2(track).to_bytes().unwrap();
+        tx.send_to(&frame, rx.local_addr().unwrap()).await.unwrap();
+        assert_eq!(recv_json(&rx, None, b"j3.2").await.unwrap(), None);
+    }
+
+    #[test]
+    fn every_centidegree_heading_survives_the_round_trip() {
+        for heading_cdeg in 0..36_000u16 {
+            let heading_deg = f64::from(heading_cdeg) / 100.0;
+            let sent = Telemetry {
+                track: 7,
+                lat: 0.0,
+                lon: 0.0,
+                alt_m: 0.0,
+                speed_ms: 0,
+                heading_deg,
+                identity_code: None,
+            };
+            let msg = crate::to_message(&sent, true, Precision::Full).unwrap();
+            assert_eq!(to_telemetry(&msg).unwrap().heading_deg, heading_deg);
+        }
+    }
+}
@@ -0,0 +1,228 @@
+//! Where inbound samples come from: Zenoh in production, scripted sources in tests.
+
+use anyhow::Result;
+use std::future::Future;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Transport-neutral copy of the parts of a sample the bridge uses.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Inbound {
+    pub payload: Vec<u8>,
+    pub encoding: String,
+    pub attachment: Option<Vec<u8>>,
+}
+
+impl Inbound {
+    /// Sample with a JSON payload and no attachment.
+    #[cfg(test)]
+    pub fn json(text: &str) -> Self {
+        Self {
+            payload: text.as_bytes().to_vec(),
+            encoding: "application/json".to_string(),
+            attachment: None,
+        }
+    }
+}
+
+#[cfg(feature = "zenoh")]
+impl From<&zenoh::sample::Sample> for Inbound {
+    fn from(sample: &zenoh::sample::Sample) -> Self {
+        Self {
+            payload: sample.payload().to_bytes().into_owned(),
+            encoding: sample.encoding().to_string(),
+            attachment: sample.attachment().map(|a| a.to_bytes().into_owned()),
+        }
+    }
+}
+
+/// An async stream of inbound samples.
+pub trait Source {
+    async fn recv(&mut self) -> Result<Inbound>;
+}
+
+#[cfg(feature = "zenoh")]
+type ZenohSubscriber =
+    zenoh::pubsub::Subscriber<zenoh::handlers::FifoChannelHandler<zenoh::sample::Sample>>;
+
+#[cfg(feature = "zenoh")]
+impl Source for ZenohSubscriber {
+    async fn recv(&mut self) -> Result<Inbound> {
+        let sample = self
+            .recv_async()
+            .await
+            .map_err(|e| anyhow::anyhow!("{}", e))?;
+        Ok(Inbound::from(&sample))
+    }
+}
+
+/// A Zenoh session together with its subscriber, so both are torn down as one.
+#[cfg(feature = "zenoh")]
+pub struct ZenohSource {
+    _session: zenoh::Session,
+    sub: ZenohSubscriber,
+}
+
+#[cfg(feature = "zenoh")]
+impl ZenohSource {
+    pub async fn open(selector: String) -> Result<Self> {
+        // Zenoh 1.x API: open() and declare_subscriber() are async and return Results directly.
+        let session = zenoh::open(zenoh::config::Config::default())
+            .await
+            .map_err(|e| anyhow::anyhow!("{}", e))?;
+        let sub = session
+            .declare_subscriber(selector)
+            .await
+            .map_err(|e| anyhow::anyhow!("{}", e))?;
+        Ok(Self {
+            _session: session,
+            sub,
+        })
+    }
+}
+
+#[cfg(feature = "zenoh")]
+impl Source for ZenohSource {
+    async fn recv(&mut self) -> Result<Inbound> {
+        self.sub.recv().await
+    }
+}
+
+/// Exponential delay between reconnect attempts: doubles from `initial` up to `max`.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    initial: Duration,
+    max: Duration,
+    next: Duration,
+}
+
+impl Backoff {
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Self {
+            initial,
+            max,
+            next: initial,
+        }
+    }
+
+    /// The delay before this attempt; the one after is twice as long, up to `max`.
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self.next;
+        self.next = (self.next * 2).min(self.max);
+        delay
+    }
+
+    pub fn reset(&mut self) {
+        self.next = self.initial;
+    }
+}
+
+/// A [`Source`] that reopens its inner source after a receive error instead of failing,
+/// with [`Backoff`] between attempts. `connect` also opens the first source, lazily.
+pub struct Reconnecting<S, C> {
+    inner: Option<S>,
+    connect: C,
+    backoff: Backoff,
+}
+
+impl<S, C, F> Reconnecting<S, C>
+where
+    C: FnMut() -> F,
+    F: Future<Output = Result<S>>,
+{
+    pub fn new(connect: C, backoff: Backoff) -> Self {
+        Self {
+            inner: None,
+            connect,
+            backoff,
+        }
+    }
+}
+
+impl<S, C, F> Source for Reconnecting<S, C>
+where
+    S: Source,
+    C: FnMut() -> F,
+    F: Future<Output = Result<S>>,
+{
+    async fn recv(&mut self) -> Result<Inbound> {
+        loop {
+            let inner = match &mut self.inner {
+                Some(inner) => inner,
+                None => match (self.connect)().await {
+                    Ok(inner) => {
+                        info!("source connected");
+                        self.backoff.reset();
+                        self.inner.insert(inner)
+                    }
+                    Err(e) => {
+                        let delay = self.backoff.next_delay();
+                        warn!(error = %e, ?delay, "connect failed; retrying");
+                        tokio::time::sleep(delay).await;
+                        continue;
+                    }
+                },
+            };
+            match inner.recv().await {
+                Ok(sample) => return Ok(sample),
+                Err(e) => {
+                    let delay = self.backoff.next_delay();
+                    warn!(error = %e, ?delay, "source failed; reconnecting");
+                    self.inner = None;
+                    tokio::time::sleep(delay).await;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    /// Yields its script in order, then never resolves.
+    struct Scripted(VecDeque<Result<Inbound>>);
+
+    impl Source for Scripted {
+        async fn recv(&mut self) -> Result<Inbound> {
+            match self.0.pop_front() {
+                Some(next) => next,
+                None => std::future::pending().await,
+            }
+        }
+    }
+
+    #[test]
+    fn backoff_doubles_to_cap_and_resets() {
+        let mut b = Backoff::new(Duration::from_millis(100), Duration::from_millis(500));
+        let delays: Vec<_> = (0..5).map(|_| b.next_delay().as_millis()).collect();
+        assert_eq!(delays, [100, 200, 400, 500, 500]);
+        b.reset();
+        assert_eq!(b.next_delay(), Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn recovers_after_subscriber_failure() {
+        let mut connects = 0;
+        let mut src = Reconnecting::new(
+            || {
+                connects += 1;
+                let script = match connects {
+                    1 => vec![Err(anyhow::anyhow!("router went away"))],
+                    2 => vec![Ok(Inbound::json("{}"))],
+                    _ => vec![],
+                };
+                async move { Ok(Scripted(script.into())) }
+            },
+            Backoff::new(Duration::from_millis(1), Duration::from_millis(4)),
+        );
+        let sample = tokio::time::timeout(Duration::from_secs(1), src.recv())
+            .await
+            .expect("recovers without hanging")
+            .unwrap();
+        assert_eq!(sample, Inbound::json("{}"));
+        drop(src);
+        assert_eq!(connects, 2);
+    }
+}
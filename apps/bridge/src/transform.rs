@@ -0,0 +1,168 @@
+//! Per-message transforms applied between telemetry decode and J-Series encode.
+
+use crate::Telemetry;
+use std::collections::BTreeSet;
+use tracing::trace;
+
+/// Rewrites a telemetry record in the pipeline; returning `None` drops the message.
+pub trait Transform: Send + Sync {
+    fn apply(&self, t: Telemetry) -> Option<Telemetry>;
+}
+
+/// Identity transform: passes every record through unchanged.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Passthrough;
+
+impl Transform for Passthrough {
+    fn apply(&self, t: Telemetry) -> Option<Telemetry> {
+        Some(t)
+    }
+}
+
+/// Shift positions by a fixed offset in degrees; longitude wraps into [-180, 180).
+#[derive(Debug, Clone, Copy)]
+pub struct CoordinateOffset {
+    pub lat_deg: f64,
+    pub lon_deg: f64,
+}
+
+impl Transform for CoordinateOffset {
+    fn apply(&self, mut t: Telemetry) -> Option<Telemetry> {
+        t.lat = (t.lat + self.lat_deg).clamp(-90.0, 90.0);
+        t.lon = (t.lon + self.lon_deg + 180.0).rem_euclid(360.0) - 180.0;
+        Some(t)
+    }
+}
+
+/// Renumber tracks by an explicit `from -> to` table; unlisted tracks pass through.
+#[derive(Debug, Clone, Default)]
+pub struct TrackRemap {
+    pub map: Vec<(u16, u16)>,
+}
+
+impl Transform for TrackRemap {
+    fn apply(&self, mut t: Telemetry) -> Option<Telemetry> {
+        if let Some(&(_, to)) = self.map.iter().find(|(from, _)| *from == t.track) {
+            t.track = to;
+        }
+        Some(t)
+    }
+}
+
+/// Forward only the listed tracks; everything else is dropped (logged at trace level).
+#[derive(Debug, Clone, Default)]
+pub struct TrackAllowlist {
+    pub tracks: BTreeSet<u16>,
+}
+
+impl Transform for TrackAllowlist {
+    fn apply(&self, t: Telemetry) -> Option<Telemetry> {
+        if self.tracks.contains(&t.track) {
+            return Some(t);
+        }
+        trace!(track = t.track, "track not in allowlist");
+        None
+    }
+}
+
+/// Ordered registry of transforms the bridge runs on every message.
+#[derive(Default)]
+pub struct Pipeline {
+    transforms: Vec<Box<dyn Transform>>,
+}
+
+impl Pipeline {
+    pub fn push(&mut self, transform: impl Transform + 'static) -> &mut Self {
+        self.transforms.push(Box::new(transform));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.transforms.is_empty()
+    }
+
+    /// Run every transform in order, stopping at the first one that drops the record.
+    pub fn apply(&self, t: Telemetry) -> Option<Telemetry> {
+        self.transforms.iter().try_fold(t, |t, x| x.apply(t))
+    }
+}
+
+/// Parse a `--remap-track` value of the form `FROM=TO`.
+pub fn parse_remap(s: &str) -> Result<(u16, u16), String> {
+    let (from, to) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected FROM=TO, got {s:?}"))?;
+    let parse = |v: &str| v.trim().parse::<u16>().map_err(|e| format!("{v:?}: {e}"));
+    Ok((parse(from)?, parse(to)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(track: u16, lat: f64, lon: f64) -> Telemetry {
+        Telemetry {
+            track,
+            lat,
+            lon,
+            alt_m: 1000.0,
+            speed_ms: 100,
+            heading_deg: 90.0,
+            identity_code: None,
+        }
+    }
+
+    #[test]
+    fn passthrough_keeps_record() {
+        let t = track(1, 10.0, 20.0);
+        assert_eq!(Passthrough.apply(t.clone()), Some(t));
+    }
+
+    #[test]
+    fn coordinate_offset_shifts_and_wraps() {
+        let offset = CoordinateOffset {
+            lat_deg: 0.5,
+            lon_deg: 10.0,
+        };
+        let t = offset.apply(track(1, 10.0, 175.0)).unwrap();
+        assert_eq!(t.lat, 10.5);
+        assert!((t.lon - -175.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn track_remap_renumbers_listed_tracks_only() {
+        let remap = TrackRemap { map: vec![(42, 7)] };
+        assert_eq!(remap.apply(track(42, 0.0, 0.0)).unwrap().track, 7);
+        assert_eq!(remap.apply(track(43, 0.0, 0.0)).unwrap().track, 43);
+        assert_eq!(parse_remap("42=7"), Ok((42, 7)));
+        assert!(parse_remap("42").is_err());
+    }
+
+    #[test]
+    fn track_allowlist_drops_unlisted_tracks() {
+        let allow = TrackAllowlist {
+            tracks: [42, 100].into(),
+        };
+        assert_eq!(allow.apply(track(42, 0.0, 0.0)).unwrap().track, 42);
+        assert_eq!(allow.apply(track(7, 0.0, 0.0)), None);
+    }
+
+    struct DropOdd;
+
+    impl Transform for DropOdd {
+        fn apply(&self, t: Telemetry) -> Option<Telemetry> {
+            t.track.is_multiple_of(2).then_some(t)
+        }
+    }
+
+    #[test]
+    fn pipeline_stops_at_dropping_transform() {
+        let mut pipeline = Pipeline::default();
+        pipeline
+            .push(DropOdd)
+            .push(TrackRemap { map: vec![(2, 3)] });
+        assert_eq!(pipeline.apply(track(2, 0.0, 0.0)).unwrap().track, 3);
+        // 3 is dropped by the first stage, not renumbered by the second.
+        assert_eq!(pipeline.apply(track(3, 0.0, 0.0)), None);
+    }
+}
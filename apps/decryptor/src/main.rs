@@ -0,0 +1,126 @@
+use anyhow::{bail, Result};
+use clap::Parser;
+use e2ee::Session;
+use jseries::JMessage;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "decryptor",
+    about = "Open captured E2EE frames (hex or base64, one per line) offline"
+)]
+struct Args {
+    /// Pre-shared key as hex (same as the bridge's --psk-hex)
+    #[arg(long, conflicts_with = "key_hex")]
+    psk_hex: Option<String>,
+    /// Raw 32-byte session key as hex
+    #[arg(long)]
+    key_hex: Option<String>,
+    /// Associated data the frames were sealed with
+    #[arg(long, default_value = "j3.2")]
+    aad: String,
+    /// Read frames from a file instead of stdin
+    #[arg(long)]
+    input: Option<PathBuf>,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let sess = match (&args.psk_hex, &args.key_hex) {
+        (Some(psk), None) => e2ee::session_from_psk(&hex::decode(psk)?),
+        (None, Some(key)) => {
+            let key: [u8; 32] = hex::decode(key)?
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("--key-hex must be 32 bytes"))?;
+            Session::from_key(key)
+        }
+        _ => bail!("one of --psk-hex or --key-hex is required"),
+    };
+
+    let input: Box<dyn BufRead> = match &args.input {
+        Some(path) => Box::new(BufReader::new(File::open(path)?)),
+        None => Box::new(io::stdin().lock()),
+    };
+    let (ok, failed) = process(input, &mut io::stdout().lock(), &sess, args.aad.as_bytes())?;
+    eprintln!("decryptor: {ok} frame(s) opened, {failed} failed");
+    Ok(())
+}
+
+/// Open every non-empty line of `input`, writing one report line per frame to `out`.
+/// Malformed lines are reported and skipped; returns `(opened, failed)` counts.
+fn process<R: BufRead, W: Write>(
+    input: R,
+    out: &mut W,
+    sess: &Session,
+    aad: &[u8],
+) -> io::Result<(usize, usize)> {
+    let (mut ok, mut failed) = (0, 0);
+    for (i, line) in input.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        match open_line(sess, aad, line) {
+            Ok(plaintext) => {
+                ok += 1;
+                match JMessage::from_bytes(&plaintext) {
+                    Ok(msg) => writeln!(out, "line {}: {msg}", i + 1)?,
+                    Err(_) => {
+                        writeln!(out, "line {}: plaintext {}", i + 1, hex::encode(&plaintext))?
+                    }
+                }
+            }
+            Err(e) => {
+                failed += 1;
+                writeln!(out, "line {}: error: {e}", i + 1)?;
+            }
+        }
+    }
+    Ok((ok, failed))
+}
+
+/// Decode a single frame, treating it as hex when it looks like hex and base64 otherwise.
+fn open_line(sess: &Session, aad: &[u8], line: &str) -> Result<Vec<u8>, e2ee::Error> {
+    match hex::decode(line) {
+        Ok(framed) => sess.open(aad, &framed),
+        Err(_) => sess.open_base64(aad, line),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jseries::J3_2AirTrack;
+
+    #[test]
+    fn process_reports_valid_and_invalid_frames() {
+        let sess = e2ee::session_from_psk(b"incident");
+        let msg = JMessage::J3_2(J3_2AirTrack::from_geo(42, 45.0, -122.0, 1500.0, 220, 271));
+        let hex_frame = hex::encode(sess.seal(b"j3.2", &msg.to_bytes().unwrap()).unwrap());
+        let b64_frame = sess.seal_base64(b"j3.2", b"not a j-series body").unwrap();
+        let other_key = hex::encode(
+            e2ee::session_from_psk(b"other")
+                .seal(b"j3.2", b"x")
+                .unwrap(),
+        );
+        let input = format!("{hex_frame}\n\nzz-not-a-frame\n{b64_frame}\n{other_key}\n");
+
+        let mut out = Vec::new();
+        let counts = process(input.as_bytes(), &mut out, &sess, b"j3.2").unwrap();
+        assert_eq!(counts, (2, 2));
+
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("line 1: J3.2 AirTrack: Track #42"));
+        assert!(lines[1].starts_with("line 3: error:"));
+        assert_eq!(
+            lines[2],
+            format!("line 4: plaintext {}", hex::encode(b"not a j-series body"))
+        );
+        assert!(lines[3].starts_with("line 5: error:"));
+    }
+}
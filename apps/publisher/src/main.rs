@@ -1,15 +1,33 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::Parser;
+use path::{FlightPath, Point};
 use serde::Serialize;
+use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::time::Duration;
+use walk::{RandomWalk, WalkBounds, WalkState};
 
-#[derive(Debug, Parser)]
+mod path;
+mod replay;
+mod walk;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum PathKind {
+    /// Repeat the same point
+    Static,
+    /// Fly from --lat/--lon to --to-lat/--to-lon over --repeat steps
+    GreatCircle,
+    /// Circle clockwise around --lat/--lon
+    Orbit,
+}
+
+#[derive(Debug, Clone, Parser)]
 #[command(
     name = "publisher",
     about = "Publish telemetry JSON to Zenoh (drone/*)"
 )]
 struct Args {
-    /// Key expression to publish
+    /// Key expression to publish; `{track}` is replaced by each track's number
     #[arg(long, default_value = "drone/uav1/telemetry")]
     key: String,
 
@@ -31,6 +49,60 @@ struct Args {
     #[arg(long)]
     heading_deg: Option<f32>,
 
+    /// Motion model; moving paths override lat/lon/heading/speed per step
+    #[arg(long, value_enum, default_value_t = PathKind::Static)]
+    path: PathKind,
+    /// Great-circle destination latitude
+    #[arg(long, default_value_t = 46.0)]
+    to_lat: f64,
+    /// Great-circle destination longitude
+    #[arg(long, default_value_t = -121.0)]
+    to_lon: f64,
+    /// Orbit radius (m)
+    #[arg(long, default_value_t = 5000.0)]
+    orbit_radius_m: f64,
+    /// Steps per full orbit
+    #[arg(long, default_value_t = 36)]
+    orbit_steps: usize,
+
+    /// Number of concurrent tracks, numbered up from --track and spaced north of --lat
+    #[arg(long, default_value_t = 1, conflicts_with_all = ["json", "csv"])]
+    tracks: u16,
+
+    /// Replay `track,lat,lon,alt_m,speed_ms,heading_deg` rows from a CSV file, one message
+    /// per row (overrides --repeat)
+    #[arg(long, conflicts_with_all = ["json", "simulate"])]
+    csv: Option<PathBuf>,
+
+    /// Random-walk from the synthesized fields instead of following --path
+    #[arg(long, conflicts_with = "json")]
+    simulate: bool,
+    /// RNG seed for --simulate; the same seed replays the same track
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+    /// Maximum heading change per second (deg/s) for --simulate
+    #[arg(long, default_value_t = 3.0)]
+    turn_rate_dps: f64,
+    /// Maximum altitude change per second (m/s) for --simulate
+    #[arg(long, default_value_t = 5.0)]
+    climb_rate_ms: f64,
+    /// Maximum speed change per second (m/s²) for --simulate
+    #[arg(long, default_value_t = 2.0)]
+    accel_ms2: f64,
+    /// Speed range (m/s) for --simulate
+    #[arg(long, default_value_t = 150.0)]
+    min_speed_ms: f64,
+    #[arg(long, default_value_t = 250.0)]
+    max_speed_ms: f64,
+
+    /// Send encoded J3.2 frames straight to this UDP sink instead of publishing JSON
+    #[arg(long, conflicts_with = "json")]
+    direct_udp: Option<SocketAddr>,
+
+    /// Seal each payload with E2EE under this PSK (hex) before publishing
+    #[arg(long)]
+    psk_hex: Option<String>,
+
     /// Number of messages to publish
     #[arg(long, default_value_t = 1)]
     repeat: usize,
@@ -39,7 +111,7 @@ struct Args {
     interval_ms: u64,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 struct TelemetryOut {
     track: u16,
     lat: f64,
@@ -49,43 +121,245 @@ struct TelemetryOut {
     heading_deg: f32,
 }
 
-fn synthesize(args: &Args) -> String {
-    if let Some(j) = &args.json {
-        return j.clone();
+/// AAD for sealed telemetry payloads; the bridge opens them with the same value.
+const AAD_TELEMETRY: &[u8] = b"telemetry";
+
+/// AAD for sealed `--direct-udp` frames, matching the bridge's outbound frames.
+const AAD_J3_2: &[u8] = b"j3.2";
+
+/// Latitude offset between consecutive `--tracks` starting points.
+const TRACK_SPACING_DEG: f64 = 0.01;
+
+/// One logical track: its own motion arguments, key and random-walk state.
+struct Track {
+    key: String,
+    args: Args,
+    walk: Option<RandomWalk>,
+}
+
+impl Track {
+    fn telemetry(&mut self, step: usize) -> TelemetryOut {
+        match self.walk.as_mut() {
+            Some(walk) => walk_telemetry(&self.args, walk.step()),
+            None => telemetry(&self.args, step),
+        }
+    }
+
+    fn payload(&mut self, step: usize) -> String {
+        match &self.args.json {
+            Some(j) => j.clone(),
+            None => serde_json::to_string(&self.telemetry(step)).unwrap(),
+        }
+    }
+}
+
+/// `--tracks` independent tracks with distinct numbers, start points and walk seeds.
+fn fleet(args: &Args) -> Vec<Track> {
+    let base = base_telemetry(args);
+    (0..args.tracks.max(1))
+        .map(|i| {
+            let mut args = args.clone();
+            let track = base.track.wrapping_add(i);
+            args.track = Some(track);
+            args.lat = Some(base.lat + TRACK_SPACING_DEG * i as f64);
+            args.seed = args.seed.wrapping_add(i as u64);
+            Track {
+                key: args.key.replace("{track}", &track.to_string()),
+                walk: random_walk(&args),
+                args,
+            }
+        })
+        .collect()
+}
+
+/// One payload per track for `step`, in track order.
+fn tick(fleet: &mut [Track], step: usize) -> Vec<(String, String)> {
+    fleet
+        .iter_mut()
+        .map(|t| (t.key.clone(), t.payload(step)))
+        .collect()
+}
+
+/// The bytes to publish for one JSON payload: sealed when a session is configured.
+fn encode_payload(sess: Option<&e2ee::Session>, json: String) -> Result<Vec<u8>> {
+    match sess {
+        Some(s) => Ok(s.seal(AAD_TELEMETRY, json.as_bytes())?),
+        None => Ok(json.into_bytes()),
+    }
+}
+
+/// Encode one point as the J3.2 frame the bridge would send, sealed when a session is
+/// configured.
+fn to_frame(t: &TelemetryOut, sess: Option<&e2ee::Session>) -> Result<Vec<u8>> {
+    let heading_cdeg = jseries::units::deg_to_cdeg(t.heading_deg.into());
+    let track = jseries::J3_2AirTrack::from_geo(
+        t.track,
+        t.lat,
+        t.lon,
+        t.alt_m as f64,
+        t.speed_ms,
+        heading_cdeg,
+    );
+    let bytes = jseries::JMessage::J3_2(track).to_bytes()?;
+    match sess {
+        Some(s) => Ok(s.seal(AAD_J3_2, &bytes)?),
+        None => Ok(bytes),
+    }
+}
+
+/// `--direct-udp`: the same points the Zenoh path would publish, as J3.2 datagrams.
+async fn send_direct(args: &Args, sess: Option<&e2ee::Session>, sink: SocketAddr) -> Result<()> {
+    let sock = tokio::net::UdpSocket::bind("0.0.0.0:0").await?;
+    let mut fleet = fleet(args);
+    let rows = args.csv.as_deref().map(replay::read_csv).transpose()?;
+    let total = rows.as_ref().map_or(args.repeat, Vec::len);
+    for i in 0..total {
+        let batch = match &rows {
+            Some(rows) => vec![rows[i].clone()],
+            None => fleet.iter_mut().map(|t| t.telemetry(i)).collect(),
+        };
+        for t in batch {
+            let frame = to_frame(&t, sess)?;
+            sock.send_to(&frame, sink).await?;
+            println!("sent [{}/{}] track {} to {}", i + 1, total, t.track, sink);
+        }
+        if i + 1 < total {
+            tokio::time::sleep(Duration::from_millis(args.interval_ms)).await;
+        }
+    }
+    Ok(())
+}
+
+fn flight_path(args: &Args) -> Option<FlightPath> {
+    let start = Point {
+        lat: args.lat.unwrap_or(45.1234567),
+        lon: args.lon.unwrap_or(-122.9876543),
+    };
+    match args.path {
+        PathKind::Static => None,
+        PathKind::GreatCircle => Some(FlightPath::GreatCircle {
+            from: start,
+            to: Point {
+                lat: args.to_lat,
+                lon: args.to_lon,
+            },
+            steps: args.repeat,
+        }),
+        PathKind::Orbit => Some(FlightPath::Orbit {
+            center: start,
+            radius_m: args.orbit_radius_m,
+            steps_per_orbit: args.orbit_steps,
+        }),
+    }
+}
+
+fn random_walk(args: &Args) -> Option<RandomWalk> {
+    if !args.simulate {
+        return None;
     }
-    let t = TelemetryOut {
+    let t = base_telemetry(args);
+    let start = WalkState {
+        point: Point {
+            lat: t.lat,
+            lon: t.lon,
+        },
+        alt_m: t.alt_m as f64,
+        heading_deg: t.heading_deg as f64,
+        speed_ms: t.speed_ms as f64,
+    };
+    let bounds = WalkBounds {
+        turn_rate_dps: args.turn_rate_dps,
+        climb_rate_ms: args.climb_rate_ms,
+        accel_ms2: args.accel_ms2,
+        min_speed_ms: args.min_speed_ms,
+        max_speed_ms: args.max_speed_ms,
+    };
+    let interval_s = args.interval_ms as f64 / 1000.0;
+    Some(RandomWalk::new(start, bounds, interval_s, args.seed))
+}
+
+fn walk_telemetry(args: &Args, s: WalkState) -> TelemetryOut {
+    TelemetryOut {
+        lat: s.point.lat,
+        lon: s.point.lon,
+        alt_m: s.alt_m.round().min(i16::MAX as f64) as i16,
+        speed_ms: s.speed_ms.round() as u16,
+        heading_deg: s.heading_deg as f32,
+        ..base_telemetry(args)
+    }
+}
+
+fn base_telemetry(args: &Args) -> TelemetryOut {
+    TelemetryOut {
         track: args.track.unwrap_or(42),
         lat: args.lat.unwrap_or(45.1234567),
         lon: args.lon.unwrap_or(-122.9876543),
         alt_m: args.alt_m.unwrap_or(1500),
         speed_ms: args.speed_ms.unwrap_or(220),
         heading_deg: args.heading_deg.unwrap_or(271.5),
-    };
-    serde_json::to_string(&t).unwrap()
+    }
+}
+
+fn telemetry(args: &Args, step: usize) -> TelemetryOut {
+    let mut t = base_telemetry(args);
+    if let Some(path) = flight_path(args) {
+        let s = path.sample(step, args.interval_ms as f64 / 1000.0);
+        t.lat = s.point.lat;
+        t.lon = s.point.lon;
+        t.heading_deg = s.heading_deg as f32;
+        t.speed_ms = s.speed_ms.round() as u16;
+    }
+    t
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
+    let sess = args
+        .psk_hex
+        .as_deref()
+        .map(e2ee::session_from_psk_hex)
+        .transpose()
+        .context("--psk-hex")?;
+    if let Some(sink) = args.direct_udp {
+        return send_direct(&args, sess.as_ref(), sink).await;
+    }
 
     #[cfg(feature = "zenoh")]
     {
         let session = zenoh::open(zenoh::Config::default())
             .await
             .map_err(|e| anyhow::anyhow!("{}", e))?;
-        let publisher = session
-            .declare_publisher(args.key.clone())
-            .await
-            .map_err(|e| anyhow::anyhow!("{}", e))?;
+        let mut fleet = fleet(&args);
+        let mut publishers = std::collections::HashMap::new();
+        for track in &fleet {
+            if !publishers.contains_key(&track.key) {
+                let publisher = session
+                    .declare_publisher(track.key.clone())
+                    .await
+                    .map_err(|e| anyhow::anyhow!("{}", e))?;
+                publishers.insert(track.key.clone(), publisher);
+            }
+        }
 
-        for i in 0..args.repeat {
-            let payload = synthesize(&args);
-            publisher
-                .put(payload)
-                .await
-                .map_err(|e| anyhow::anyhow!("{}", e))?;
-            println!("published [{}/{}] to {}", i + 1, args.repeat, args.key);
-            if i + 1 < args.repeat {
+        let rows = args.csv.as_deref().map(replay::read_csv).transpose()?;
+        let total = rows.as_ref().map_or(args.repeat, Vec::len);
+        for i in 0..total {
+            let batch = match &rows {
+                Some(rows) => vec![(
+                    fleet[0].key.clone(),
+                    serde_json::to_string(&rows[i]).unwrap(),
+                )],
+                None => tick(&mut fleet, i),
+            };
+            for (key, payload) in batch {
+                publishers[&key]
+                    .put(encode_payload(sess.as_ref(), payload)?)
+                    .await
+                    .map_err(|e| anyhow::anyhow!("{}", e))?;
+                println!("published [{}/{}] to {}", i + 1, total, key);
+            }
+            if i + 1 < total {
                 tokio::time::sleep(Duration::from_millis(args.interval_ms)).await;
             }
         }
@@ -98,3 +372,105 @@ async fn main() -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sealed_payload_opens_to_json() {
+        let json = r#"{"track":42,"lat":45.0}"#.to_string();
+        let sess = e2ee::session_from_psk_hex("00112233").unwrap();
+        let sealed = encode_payload(Some(&sess), json.clone()).unwrap();
+        assert_ne!(sealed, json.as_bytes());
+        assert_eq!(sess.open(AAD_TELEMETRY, &sealed).unwrap(), json.as_bytes());
+        assert!(sess.open(b"j3.2", &sealed).is_err());
+        assert_eq!(
+            encode_payload(None, json.clone()).unwrap(),
+            json.into_bytes()
+        );
+    }
+
+    #[tokio::test]
+    async fn direct_udp_frames_parse_back_to_track() {
+        let sink = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = sink.local_addr().unwrap().to_string();
+        let args = Args::parse_from([
+            "publisher",
+            "--direct-udp",
+            &addr,
+            "--track",
+            "42",
+            "--lat",
+            "45.0",
+            "--lon=-122.0",
+            "--heading-deg",
+            "271.5",
+        ]);
+        send_direct(&args, None, args.direct_udp.unwrap())
+            .await
+            .unwrap();
+        let mut buf = [0u8; 64];
+        let n = sink.recv(&mut buf).await.unwrap();
+        let expected = jseries::J3_2AirTrack::from_geo(42, 45.0, -122.0, 1500.0, 220, 27_150);
+        assert_eq!(
+            jseries::JMessage::from_bytes(&buf[..n]).unwrap(),
+            jseries::JMessage::J3_2(expected)
+        );
+    }
+
+    #[test]
+    fn frame_matches_bridge_bytes() {
+        let t = TelemetryOut {
+            track: 42,
+            lat: 45.0,
+            lon: -122.0,
+            alt_m: 1500,
+            speed_ms: 220,
+            heading_deg: 271.0,
+        };
+        // What the bridge sends for this telemetry (pinned in its `--once` test).
+        let frame = to_frame(&t, None).unwrap();
+        assert_eq!(
+            frame.iter().map(|b| format!("{b:02x}")).collect::<String>(),
+            "32002abfffe527d00a80c50006e34ee0"
+        );
+    }
+
+    #[test]
+    fn one_tick_covers_every_track() {
+        for extra in [&[][..], &["--simulate"][..]] {
+            let args = Args::parse_from(
+                [
+                    "publisher",
+                    "--tracks",
+                    "3",
+                    "--track",
+                    "7",
+                    "--key",
+                    "drone/{track}/telemetry",
+                ]
+                .iter()
+                .chain(extra),
+            );
+            let batch = tick(&mut fleet(&args), 0);
+            let tracks: Vec<u64> = batch
+                .iter()
+                .map(|(_, json)| {
+                    let v: serde_json::Value = serde_json::from_str(json).unwrap();
+                    v["track"].as_u64().unwrap()
+                })
+                .collect();
+            assert_eq!(tracks, [7, 8, 9]);
+            let keys: Vec<&str> = batch.iter().map(|(k, _)| k.as_str()).collect();
+            assert_eq!(
+                keys,
+                [
+                    "drone/7/telemetry",
+                    "drone/8/telemetry",
+                    "drone/9/telemetry"
+                ]
+            );
+        }
+    }
+}
@@ -0,0 +1,193 @@
+//! Synthetic flight paths, each a pure function of the step index.
+
+use std::f64::consts::PI;
+
+const EARTH_RADIUS_M: f64 = 6_371_000.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+    pub lat: f64,
+    pub lon: f64,
+}
+
+/// Position and kinematics of the track at one step.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    pub point: Point,
+    pub heading_deg: f64,
+    pub speed_ms: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FlightPath {
+    /// Constant-speed travel from `from` to `to`, arriving on the last step.
+    GreatCircle {
+        from: Point,
+        to: Point,
+        steps: usize,
+    },
+    /// Clockwise circle around `center`, completing one lap every `steps_per_orbit` steps.
+    Orbit {
+        center: Point,
+        radius_m: f64,
+        steps_per_orbit: usize,
+    },
+}
+
+impl FlightPath {
+    /// Where the track is at `step`, with `interval_s` seconds between steps.
+    pub fn sample(&self, step: usize, interval_s: f64) -> Sample {
+        match *self {
+            FlightPath::GreatCircle { from, to, steps } => {
+                let legs = steps.saturating_sub(1).max(1) as f64;
+                let f = (step as f64 / legs).min(1.0);
+                let point = slerp(from, to, f);
+                let heading_deg = if f < 1.0 {
+                    bearing_deg(point, to)
+                } else {
+                    (bearing_deg(to, from) + 180.0) % 360.0
+                };
+                Sample {
+                    point,
+                    heading_deg,
+                    speed_ms: distance_m(from, to) / (legs * interval_s),
+                }
+            }
+            FlightPath::Orbit {
+                center,
+                radius_m,
+                steps_per_orbit,
+            } => {
+                let laps = step as f64 / steps_per_orbit.max(1) as f64;
+                let theta = 360.0 * laps.fract();
+                Sample {
+                    point: destination(center, theta, radius_m),
+                    heading_deg: (theta + 90.0) % 360.0,
+                    speed_ms: 2.0 * PI * radius_m / (steps_per_orbit.max(1) as f64 * interval_s),
+                }
+            }
+        }
+    }
+}
+
+/// Great-circle distance (haversine).
+pub fn distance_m(a: Point, b: Point) -> f64 {
+    let (la1, la2) = (a.lat.to_radians(), b.lat.to_radians());
+    let dlat = la2 - la1;
+    let dlon = (b.lon - a.lon).to_radians();
+    let h = (dlat / 2.0).sin().powi(2) + la1.cos() * la2.cos() * (dlon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_M * h.sqrt().asin()
+}
+
+/// Initial bearing from `a` to `b` in [0, 360).
+pub fn bearing_deg(a: Point, b: Point) -> f64 {
+    let (la1, la2) = (a.lat.to_radians(), b.lat.to_radians());
+    let dlon = (b.lon - a.lon).to_radians();
+    let y = dlon.sin() * la2.cos();
+    let x = la1.cos() * la2.sin() - la1.sin() * la2.cos() * dlon.cos();
+    y.atan2(x).to_degrees().rem_euclid(360.0)
+}
+
+/// The point `distance_m` from `origin` along the initial `bearing_deg`.
+pub fn destination(origin: Point, bearing_deg: f64, distance_m: f64) -> Point {
+    let d = distance_m / EARTH_RADIUS_M;
+    let (lat1, lon1, brg) = (
+        origin.lat.to_radians(),
+        origin.lon.to_radians(),
+        bearing_deg.to_radians(),
+    );
+    let lat2 = (lat1.sin() * d.cos() + lat1.cos() * d.sin() * brg.cos()).asin();
+    let lon2 = lon1 + (brg.sin() * d.sin() * lat1.cos()).atan2(d.cos() - lat1.sin() * lat2.sin());
+    Point {
+        lat: lat2.to_degrees(),
+        lon: (lon2.to_degrees() + 540.0).rem_euclid(360.0) - 180.0,
+    }
+}
+
+fn to_vec(p: Point) -> [f64; 3] {
+    let (lat, lon) = (p.lat.to_radians(), p.lon.to_radians());
+    [lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin()]
+}
+
+/// Spherical linear interpolation along the great circle from `a` to `b`.
+fn slerp(a: Point, b: Point, f: f64) -> Point {
+    let (va, vb) = (to_vec(a), to_vec(b));
+    let dot = (va[0] * vb[0] + va[1] * vb[1] + va[2] * vb[2]).clamp(-1.0, 1.0);
+    let omega = dot.acos();
+    if omega < 1e-12 {
+        return a;
+    }
+    let (wa, wb) = (
+        ((1.0 - f) * omega).sin() / omega.sin(),
+        (f * omega).sin() / omega.sin(),
+    );
+    let v = [
+        wa * va[0] + wb * vb[0],
+        wa * va[1] + wb * vb[1],
+        wa * va[2] + wb * vb[2],
+    ];
+    Point {
+        lat: v[2].atan2((v[0] * v[0] + v[1] * v[1]).sqrt()).to_degrees(),
+        lon: v[1].atan2(v[0]).to_degrees(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn angle_diff(a: f64, b: f64) -> f64 {
+        let d = (a - b).rem_euclid(360.0);
+        d.min(360.0 - d)
+    }
+
+    #[test]
+    fn great_circle_advances_and_heads_along_track() {
+        let from = Point {
+            lat: 45.0,
+            lon: -122.0,
+        };
+        let to = Point {
+            lat: 47.5,
+            lon: -118.0,
+        };
+        let path = FlightPath::GreatCircle {
+            from,
+            to,
+            steps: 20,
+        };
+
+        let samples: Vec<_> = (0..20).map(|i| path.sample(i, 1.0)).collect();
+        assert_eq!(samples[0].point, from);
+        assert!(distance_m(samples[19].point, to) < 1.0);
+        for w in samples.windows(2) {
+            assert!(distance_m(from, w[1].point) > distance_m(from, w[0].point));
+            assert!(distance_m(w[1].point, to) < distance_m(w[0].point, to));
+            let travel = bearing_deg(w[0].point, w[1].point);
+            assert!(angle_diff(travel, w[0].heading_deg) < 0.5);
+            let stepped = distance_m(w[0].point, w[1].point);
+            assert!((stepped - w[0].speed_ms).abs() / stepped < 1e-6);
+        }
+    }
+
+    #[test]
+    fn orbit_stays_on_radius_and_heads_tangentially() {
+        let center = Point {
+            lat: 10.0,
+            lon: 20.0,
+        };
+        let path = FlightPath::Orbit {
+            center,
+            radius_m: 5_000.0,
+            steps_per_orbit: 72,
+        };
+        for i in 0..72 {
+            let (a, b) = (path.sample(i, 2.0), path.sample(i + 1, 2.0));
+            assert!((distance_m(center, a.point) - 5_000.0).abs() < 1.0);
+            // Direction of travel is midway between the tangents at both ends of the step.
+            let travel = bearing_deg(a.point, b.point);
+            assert!(angle_diff(travel, a.heading_deg + 2.5) < 0.5);
+        }
+        assert_eq!(path.sample(72, 2.0).point, path.sample(0, 2.0).point);
+    }
+}
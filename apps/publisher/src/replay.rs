@@ -0,0 +1,109 @@
+//! Recorded flights as CSV: one `track,lat,lon,alt_m,speed_ms,heading_deg` row per message.
+
+use crate::TelemetryOut;
+use anyhow::{bail, Context, Result};
+use std::path::Path;
+use std::str::FromStr;
+
+const COLUMNS: [&str; 6] = ["track", "lat", "lon", "alt_m", "speed_ms", "heading_deg"];
+
+pub fn read_csv(path: &Path) -> Result<Vec<TelemetryOut>> {
+    let text =
+        std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    parse_csv(&text).with_context(|| format!("parsing {}", path.display()))
+}
+
+/// Parse every row, failing on the first malformed one with its 1-based line number. Blank
+/// lines are skipped, as is a leading header row naming the columns.
+pub fn parse_csv(text: &str) -> Result<Vec<TelemetryOut>> {
+    let mut rows = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || (rows.is_empty() && line.starts_with(COLUMNS[0])) {
+            continue;
+        }
+        rows.push(parse_row(line).with_context(|| format!("line {}", i + 1))?);
+    }
+    Ok(rows)
+}
+
+fn parse_row(line: &str) -> Result<TelemetryOut> {
+    let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+    if fields.len() != COLUMNS.len() {
+        bail!("expected {} fields, got {}", COLUMNS.len(), fields.len());
+    }
+    Ok(TelemetryOut {
+        track: field(&fields, 0)?,
+        lat: field(&fields, 1)?,
+        lon: field(&fields, 2)?,
+        alt_m: field(&fields, 3)?,
+        speed_ms: field(&fields, 4)?,
+        heading_deg: field(&fields, 5)?,
+    })
+}
+
+fn field<T: FromStr>(fields: &[&str], i: usize) -> Result<T>
+where
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    fields[i]
+        .parse()
+        .with_context(|| format!("bad {} {:?}", COLUMNS[i], fields[i]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FLIGHT: &str = "\
+track,lat,lon,alt_m,speed_ms,heading_deg
+42,45.1234567,-122.9876543,1500,220,271.5
+
+42, 45.2, -122.8, 1520, 225, 272
+7,-33.9,151.2,0,0,0
+";
+
+    #[test]
+    fn parses_fixture_rows() {
+        assert_eq!(
+            parse_csv(FLIGHT).unwrap(),
+            vec![
+                TelemetryOut {
+                    track: 42,
+                    lat: 45.1234567,
+                    lon: -122.9876543,
+                    alt_m: 1500,
+                    speed_ms: 220,
+                    heading_deg: 271.5,
+                },
+                TelemetryOut {
+                    track: 42,
+                    lat: 45.2,
+                    lon: -122.8,
+                    alt_m: 1520,
+                    speed_ms: 225,
+                    heading_deg: 272.0,
+                },
+                TelemetryOut {
+                    track: 7,
+                    lat: -33.9,
+                    lon: 151.2,
+                    alt_m: 0,
+                    speed_ms: 0,
+                    heading_deg: 0.0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_malformed_rows_by_line() {
+        let err = parse_csv("42,45,-122,1500,220,271\n42,45,-122,high,220,271\n").unwrap_err();
+        assert_eq!(
+            format!("{err:#}"),
+            "line 2: bad alt_m \"high\": invalid digit found in string"
+        );
+        let err = parse_csv("track,lat,lon,alt_m,speed_ms,heading_deg\n42,45,-122\n").unwrap_err();
+        assert_eq!(format!("{err:#}"), "line 2: expected 6 fields, got 3");
+    }
+}
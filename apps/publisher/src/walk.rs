@@ -0,0 +1,130 @@
+//! Seeded random-walk flight: each step nudges heading, altitude and speed within rate
+//! limits, then flies the new heading for one interval.
+
+use crate::path::{destination, Point};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Per-second rate limits for [`RandomWalk`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WalkBounds {
+    pub turn_rate_dps: f64,
+    pub climb_rate_ms: f64,
+    pub accel_ms2: f64,
+    pub min_speed_ms: f64,
+    pub max_speed_ms: f64,
+}
+
+/// Kinematic state of the walk after a step.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WalkState {
+    pub point: Point,
+    pub alt_m: f64,
+    pub heading_deg: f64,
+    pub speed_ms: f64,
+}
+
+pub struct RandomWalk {
+    state: WalkState,
+    bounds: WalkBounds,
+    interval_s: f64,
+    rng: StdRng,
+}
+
+impl RandomWalk {
+    /// Start at `start`; the same `seed` always yields the same track. The starting speed
+    /// is clamped into the bounds.
+    pub fn new(start: WalkState, bounds: WalkBounds, interval_s: f64, seed: u64) -> Self {
+        let mut state = start;
+        state.speed_ms = state
+            .speed_ms
+            .clamp(bounds.min_speed_ms, bounds.max_speed_ms);
+        Self {
+            state,
+            bounds,
+            interval_s,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// The current state, then advance one interval.
+    pub fn step(&mut self) -> WalkState {
+        let current = self.state;
+        let dt = self.interval_s;
+        let b = self.bounds;
+        let s = &mut self.state;
+        s.heading_deg =
+            (s.heading_deg + jitter(&mut self.rng, b.turn_rate_dps * dt)).rem_euclid(360.0);
+        s.alt_m = (s.alt_m + jitter(&mut self.rng, b.climb_rate_ms * dt)).max(0.0);
+        s.speed_ms = (s.speed_ms + jitter(&mut self.rng, b.accel_ms2 * dt))
+            .clamp(b.min_speed_ms, b.max_speed_ms);
+        s.point = destination(s.point, s.heading_deg, s.speed_ms * dt);
+        current
+    }
+}
+
+/// Uniform in `[-max, max]`.
+fn jitter(rng: &mut StdRng, max: f64) -> f64 {
+    if max > 0.0 {
+        rng.random_range(-max..=max)
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::distance_m;
+
+    const BOUNDS: WalkBounds = WalkBounds {
+        turn_rate_dps: 3.0,
+        climb_rate_ms: 5.0,
+        accel_ms2: 2.0,
+        min_speed_ms: 150.0,
+        max_speed_ms: 250.0,
+    };
+
+    fn start() -> WalkState {
+        WalkState {
+            point: Point {
+                lat: 45.0,
+                lon: -122.0,
+            },
+            alt_m: 1500.0,
+            heading_deg: 359.0,
+            speed_ms: 220.0,
+        }
+    }
+
+    #[test]
+    fn steps_stay_within_bounds() {
+        let dt = 2.0;
+        let mut walk = RandomWalk::new(start(), BOUNDS, dt, 7);
+        let states: Vec<_> = (0..1_000).map(|_| walk.step()).collect();
+        assert_eq!(states[0], start());
+        for w in states.windows(2) {
+            let (a, b) = (w[0], w[1]);
+            let turn = (b.heading_deg - a.heading_deg + 540.0).rem_euclid(360.0) - 180.0;
+            assert!(
+                turn.abs() <= BOUNDS.turn_rate_dps * dt + 1e-9,
+                "turn {turn}"
+            );
+            assert!((b.alt_m - a.alt_m).abs() <= BOUNDS.climb_rate_ms * dt + 1e-9);
+            assert!((b.speed_ms - a.speed_ms).abs() <= BOUNDS.accel_ms2 * dt + 1e-9);
+            assert!((BOUNDS.min_speed_ms..=BOUNDS.max_speed_ms).contains(&b.speed_ms));
+            let moved = distance_m(a.point, b.point);
+            assert!((moved - b.speed_ms * dt).abs() < 1e-3, "moved {moved}");
+        }
+    }
+
+    #[test]
+    fn same_seed_same_track() {
+        let run = |seed| {
+            let mut walk = RandomWalk::new(start(), BOUNDS, 1.0, seed);
+            (0..50).map(|_| walk.step()).collect::<Vec<_>>()
+        };
+        assert_eq!(run(1), run(1));
+        assert_ne!(run(1), run(2));
+    }
+}
@@ -0,0 +1,62 @@
+//! Criterion throughput of `seal`/`open` and their in-place variants, per algorithm and
+//! payload size. Compare AES-GCM with ChaCha20-Poly1305 on machines without AES-NI.
+//!
+//! Run with `cargo bench -p e2ee --bench e2ee`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use e2ee::{Algorithm, Session};
+
+const SIZES: [usize; 3] = [64, 1024, 64 * 1024];
+const ALGORITHMS: [Algorithm; 4] = [
+    Algorithm::Aes256Gcm,
+    Algorithm::Aes128Gcm,
+    Algorithm::ChaCha20Poly1305,
+    Algorithm::XChaCha20Poly1305,
+];
+const AAD: &[u8] = b"j3.2";
+
+fn seal_open(c: &mut Criterion) {
+    for op in ["seal", "open", "seal_in_place", "open_in_place"] {
+        let mut group = c.benchmark_group(op);
+        for alg in ALGORITHMS {
+            let session = Session::from_key_with(alg, [0x42; 32]);
+            for size in SIZES {
+                let payload = vec![0x32u8; size];
+                let frame = session.seal(AAD, &payload).unwrap();
+                let id = BenchmarkId::new(alg.name(), size);
+                group.throughput(Throughput::Bytes(size as u64));
+                match op {
+                    "seal" => group
+                        .bench_function(id, |b| b.iter(|| session.seal(AAD, &payload).unwrap())),
+                    "open" => {
+                        group.bench_function(id, |b| b.iter(|| session.open(AAD, &frame).unwrap()))
+                    }
+                    "seal_in_place" => {
+                        let mut buf = Vec::with_capacity(frame.len());
+                        group.bench_function(id, |b| {
+                            b.iter(|| {
+                                buf.clear();
+                                buf.extend_from_slice(&payload);
+                                session.seal_in_place(AAD, &mut buf).unwrap();
+                            })
+                        })
+                    }
+                    _ => {
+                        let mut buf = Vec::with_capacity(frame.len());
+                        group.bench_function(id, |b| {
+                            b.iter(|| {
+                                buf.clear();
+                                buf.extend_from_slice(&frame);
+                                session.open_in_place(AAD, &mut buf).unwrap();
+                            })
+                        })
+                    }
+                };
+            }
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, seal_open);
+criterion_main!(benches);
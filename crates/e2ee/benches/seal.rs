@@ -0,0 +1,67 @@
+//! Allocating vs in-place sealing: messages/s and heap allocations per message.
+//!
+//! Run with `cargo bench -p e2ee --bench seal`.
+
+use e2ee::session_from_psk;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+struct Counting;
+
+static ALLOCS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+const MESSAGES: usize = 200_000;
+const PAYLOAD: &[u8] = &[0x32; 16];
+
+fn run(name: &str, mut seal_one: impl FnMut()) {
+    let allocs = ALLOCS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for _ in 0..MESSAGES {
+        seal_one();
+    }
+    let elapsed = start.elapsed().max(Duration::from_nanos(1));
+    let allocs = ALLOCS.load(Ordering::Relaxed) - allocs;
+    println!(
+        "{name:>14}: {:>10.0} msg/s, {:.2} allocs/msg, {:>12.0} allocs/s",
+        MESSAGES as f64 / elapsed.as_secs_f64(),
+        allocs as f64 / MESSAGES as f64,
+        allocs as f64 / elapsed.as_secs_f64(),
+    );
+}
+
+fn main() {
+    let sess = session_from_psk(b"bench");
+
+    run("seal", || {
+        let frame = sess.seal(b"j3.2", PAYLOAD).unwrap();
+        std::hint::black_box(frame);
+    });
+
+    let mut buf = Vec::with_capacity(64);
+    run("seal_in_place", || {
+        buf.clear();
+        buf.extend_from_slice(PAYLOAD);
+        sess.seal_in_place(b"j3.2", &mut buf).unwrap();
+        std::hint::black_box(&buf);
+    });
+}
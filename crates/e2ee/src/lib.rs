@@ -1,91 +1,2831 @@
 //! Application-level E2EE scaffold.
-//! - Symmetric encryption via AES-GCM.
+//! - Symmetric encryption via AES-256/128-GCM, ChaCha20-Poly1305 or XChaCha20-Poly1305 (`Algorithm`).
 //! - Hook points for rustls-based session key derivation (feature = "rustls").
+//! - Keyed BLAKE3 chaining across frames to detect drops/reordering (`FrameChain`).
+//! - Base64 text framing for text-only transports (feature = "base64").
+//! - Ephemeral X25519 handshake without TLS (feature = "x25519").
+//! - Encrypt-then-sign with Ed25519 detached signatures (feature = "ed25519").
+//! - Argon2id password-based sessions (feature = "argon2").
+//! - zstd compression before sealing (feature = "compress").
+//! - `ring::aead` in place of RustCrypto for AES-GCM and ChaCha20-Poly1305 (feature = "ring-backend").
+//! - Counter-based nonces for guaranteed uniqueness (`CounterSession`).
+//! - Sequence-numbered frames with a sliding replay window (`ReplayWindow`).
+//! - Epoch-tagged key rotation (`KeyRing`).
+//! - Forward-secret per-message key ratchet with bounded skip (`Ratchet`).
+//! - Multi-recipient envelopes (`seal_envelope`/`open_envelope`).
+//! - Sealed-sender frames whose key-id is masked per frame (`seal_sealed_sender`).
+//! - Versioned frames with a magic/version/algorithm header (`seal_framed`/`open_framed`).
+//! - Chunked STREAM encryption for payloads larger than memory (`SealStream`/`OpenStream`).
+//! - `no_std` + `alloc` with `default-features = false`: the OS-RNG conveniences are
+//!   dropped and callers pass their own RNG to `Session::seal_with_rng` (feature = "std").
 
-use aes_gcm::aead::rand_core::RngCore;
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use aes_gcm::aead::rand_core::{CryptoRng, RngCore};
+#[cfg(feature = "std")]
+use aes_gcm::aead::OsRng;
 use aes_gcm::{
-    aead::{Aead, KeyInit, OsRng},
-    Aes256Gcm, Nonce,
+    aead::{
+        generic_array::GenericArray,
+        stream::{DecryptorBE32, EncryptorBE32},
+        Aead, AeadInPlace, KeyInit, Payload,
+    },
+    Aes128Gcm, Aes256Gcm, Nonce,
 };
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use chacha20poly1305::{ChaCha20Poly1305, XChaCha20Poly1305, XNonce};
 use thiserror::Error;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+/// The `rand_core` version the `*_with_rng` methods take, for callers wiring their own RNG.
+pub use aes_gcm::aead::rand_core;
+
+/// Fixed AAD binding wrapped keys to their purpose.
+const KEY_WRAP_AAD: &[u8] = b"ads-e2ee key wrap v1";
+/// Tag length shared by every `Algorithm`.
+const TAG_LEN: usize = 16;
+/// Widest nonce of any `Algorithm`.
+const MAX_NONCE_LEN: usize = 24;
+/// Domain label mixed into beacon AAD so beacon tags never verify as `seal` frames.
+const BEACON_LABEL: &[u8] = b"ads-e2ee beacon v1";
+/// Timestamp and length of a `seal_with_header` frame.
+const HEADER_LEN: usize = 12;
+/// Leading bytes of a `seal_framed` frame.
+const FRAME_MAGIC: &[u8; 4] = b"AE2E";
+/// Header version written by `seal_framed`; `open_framed` rejects any other.
+const FRAME_VERSION: u8 = 1;
+/// Magic, version and algorithm id.
+const FRAME_HEADER_LEN: usize = 6;
 
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("encryption failed")]
     Encrypt,
+    /// Catch-all for frames that can't be opened for another reason (e.g. not addressed to
+    /// this key); see `Truncated` and `AuthFailed` for the specific causes.
     #[error("decryption failed")]
     Decrypt,
+    #[error("frame truncated")]
+    Truncated,
+    /// Tag verification failed: wrong key, wrong AAD, or a corrupted frame.
+    #[error("authentication failed")]
+    AuthFailed,
+    #[error("invalid base64 frame")]
+    Base64,
+    #[error("handshake failed")]
+    Handshake,
+    #[error("frame chain broken")]
+    ChainBroken,
+    #[error("invalid key material")]
+    InvalidKey,
+    #[error("unknown algorithm: {0}")]
+    UnknownAlgorithm(String),
+    #[error("bad signature")]
+    BadSignature,
+    #[error("unknown key epoch: {0}")]
+    UnknownEpoch(u8),
+    #[error("nonce counter exhausted")]
+    NonceExhausted,
+    #[error("replayed or stale sequence number: {0}")]
+    Replay(u64),
+    #[error("invalid compressed payload")]
+    Decompress,
+    #[error("decompressed payload exceeds {0} bytes")]
+    DecompressLimit(usize),
+    /// Missing magic or otherwise malformed `seal_framed` header.
+    #[error("invalid frame header")]
+    InvalidHeader,
+    #[error("unsupported frame version: {0}")]
+    UnsupportedVersion(u8),
+    #[error("frame timestamp {0} is older than the allowed age")]
+    Expired(u64),
+    #[error("ratchet index {0} is beyond the skip window")]
+    RatchetSkip(u64),
+}
+
+/// AEAD algorithm used by a `Session`. All use a 16-byte tag; see `nonce_len` for the nonce.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    Aes256Gcm,
+    /// 128-bit key, for peers that only accept AES-128.
+    Aes128Gcm,
+    ChaCha20Poly1305,
+    /// 24-byte nonces, so random nonces stay collision-safe for long-lived, high-volume keys.
+    XChaCha20Poly1305,
+}
+
+impl Algorithm {
+    /// Look up an algorithm by its config name (case-insensitive).
+    pub fn from_name(name: &str) -> Result<Self, Error> {
+        match name.to_ascii_lowercase().as_str() {
+            "aes256gcm" => Ok(Algorithm::Aes256Gcm),
+            "aes128gcm" => Ok(Algorithm::Aes128Gcm),
+            "chacha20poly1305" => Ok(Algorithm::ChaCha20Poly1305),
+            "xchacha20poly1305" => Ok(Algorithm::XChaCha20Poly1305),
+            _ => Err(Error::UnknownAlgorithm(name.to_string())),
+        }
+    }
+
+    /// Config name accepted by `from_name`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::Aes256Gcm => "aes256gcm",
+            Algorithm::Aes128Gcm => "aes128gcm",
+            Algorithm::ChaCha20Poly1305 => "chacha20poly1305",
+            Algorithm::XChaCha20Poly1305 => "xchacha20poly1305",
+        }
+    }
+
+    /// Nonce width in bytes, i.e. the length of the nonce prefix on sealed frames.
+    pub fn nonce_len(self) -> usize {
+        match self {
+            Algorithm::Aes256Gcm | Algorithm::Aes128Gcm | Algorithm::ChaCha20Poly1305 => 12,
+            Algorithm::XChaCha20Poly1305 => 24,
+        }
+    }
+
+    /// Key length in bytes.
+    pub fn key_len(self) -> usize {
+        match self {
+            Algorithm::Aes128Gcm => 16,
+            _ => 32,
+        }
+    }
+
+    /// Wire id carried in `seal_framed` headers.
+    pub fn id(self) -> u8 {
+        match self {
+            Algorithm::Aes256Gcm => 1,
+            Algorithm::Aes128Gcm => 2,
+            Algorithm::ChaCha20Poly1305 => 3,
+            Algorithm::XChaCha20Poly1305 => 4,
+        }
+    }
+
+    /// Inverse of `id`.
+    pub fn from_id(id: u8) -> Result<Self, Error> {
+        match id {
+            1 => Ok(Algorithm::Aes256Gcm),
+            2 => Ok(Algorithm::Aes128Gcm),
+            3 => Ok(Algorithm::ChaCha20Poly1305),
+            4 => Ok(Algorithm::XChaCha20Poly1305),
+            _ => Err(Error::UnknownAlgorithm(id.to_string())),
+        }
+    }
 }
 
 /// Opaque session for encrypt/decrypt of payloads.
+///
+/// The key is wiped when the session (or any clone of it) is dropped.
 #[derive(Clone)]
 pub struct Session {
-    key: aes_gcm::Key<aes_gcm::aes::Aes256>,
+    algorithm: Algorithm,
+    /// Only the first `algorithm.key_len()` bytes are key; the rest is zero.
+    key: Zeroizing<[u8; 32]>,
+}
+
+impl ZeroizeOnDrop for Session {}
+
+/// Metadata of a frame decrypted by `Session::open_with_meta`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameMeta {
+    pub algorithm: Algorithm,
+    pub key_id: [u8; 8],
+    pub nonce: Vec<u8>,
 }
 
 impl Session {
-    /// Construct from a 32-byte key.
+    /// Construct from a 32-byte key, using AES-256-GCM.
     pub fn from_key(key: [u8; 32]) -> Self {
-        Self { key: key.into() }
+        Self::from_key_with(Algorithm::Aes256Gcm, key)
+    }
+
+    /// Construct from a 32-byte key for an explicit algorithm. For `Aes128Gcm` only the
+    /// first 16 bytes are used.
+    pub fn from_key_with(algorithm: Algorithm, mut key: [u8; 32]) -> Self {
+        let mut stored = Zeroizing::new(key);
+        stored[algorithm.key_len()..].fill(0);
+        key.zeroize();
+        Self {
+            algorithm,
+            key: stored,
+        }
     }
 
-    /// Encrypt a payload with a random nonce (12 bytes) prepended to the ciphertext.
+    /// Construct from a 16-byte key, using AES-128-GCM. Frames are the same shape as
+    /// AES-256-GCM ones; opening one with the other fails with `Error::AuthFailed`.
+    pub fn from_key_128(key: [u8; 16]) -> Self {
+        let mut padded = Zeroizing::new([0u8; 32]);
+        padded[..16].copy_from_slice(&key);
+        Self::from_key_with(Algorithm::Aes128Gcm, *padded)
+    }
+
+    /// Construct from config values: a key of the algorithm's `key_len` and an algorithm
+    /// name such as `"aes256gcm"` or `"chacha20poly1305"`.
+    pub fn from_config(key: &[u8], alg_name: &str) -> Result<Self, Error> {
+        let algorithm = Algorithm::from_name(alg_name)?;
+        if key.len() != algorithm.key_len() {
+            return Err(Error::InvalidKey);
+        }
+        let mut padded = Zeroizing::new([0u8; 32]);
+        padded[..key.len()].copy_from_slice(key);
+        Ok(Self::from_key_with(algorithm, *padded))
+    }
+
+    /// Construct an AES-256-GCM session from a 64-character hex key (either case).
+    /// Malformed hex or a key that isn't 32 bytes is `Error::InvalidKey`.
+    pub fn from_hex(hex: &str) -> Result<Self, Error> {
+        let bytes = decode_hex(hex)?;
+        let key: [u8; 32] = bytes.as_slice().try_into().map_err(|_| Error::InvalidKey)?;
+        Ok(Self::from_key(key))
+    }
+
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// The live key bytes (16 for AES-128-GCM, otherwise 32).
+    fn key_bytes(&self) -> &[u8] {
+        &self.key[..self.algorithm.key_len()]
+    }
+
+    fn encrypt(&self, nonce: &[u8], payload: Payload<'_, '_>) -> Result<Vec<u8>, Error> {
+        #[cfg(feature = "ring-backend")]
+        if ring_backend::supports(self.algorithm) {
+            let mut out = payload.msg.to_vec();
+            let tag = self.encrypt_detached(nonce, payload.aad, &mut out)?;
+            out.extend_from_slice(&tag);
+            return Ok(out);
+        }
+        let key = GenericArray::from_slice(&self.key[..]);
+        match self.algorithm {
+            Algorithm::Aes256Gcm => Aes256Gcm::new(key).encrypt(Nonce::from_slice(nonce), payload),
+            Algorithm::Aes128Gcm => Aes128Gcm::new(GenericArray::from_slice(self.key_bytes()))
+                .encrypt(Nonce::from_slice(nonce), payload),
+            Algorithm::ChaCha20Poly1305 => {
+                ChaCha20Poly1305::new(key).encrypt(Nonce::from_slice(nonce), payload)
+            }
+            Algorithm::XChaCha20Poly1305 => {
+                XChaCha20Poly1305::new(key).encrypt(XNonce::from_slice(nonce), payload)
+            }
+        }
+        .map_err(|_| Error::Encrypt)
+    }
+
+    fn decrypt(&self, nonce: &[u8], payload: Payload<'_, '_>) -> Result<Vec<u8>, Error> {
+        #[cfg(feature = "ring-backend")]
+        if ring_backend::supports(self.algorithm) {
+            let split = payload
+                .msg
+                .len()
+                .checked_sub(TAG_LEN)
+                .ok_or(Error::AuthFailed)?;
+            let (ct, tag) = payload.msg.split_at(split);
+            let mut out = ct.to_vec();
+            self.decrypt_detached(nonce, payload.aad, &mut out, tag)?;
+            return Ok(out);
+        }
+        let key = GenericArray::from_slice(&self.key[..]);
+        match self.algorithm {
+            Algorithm::Aes256Gcm => Aes256Gcm::new(key).decrypt(Nonce::from_slice(nonce), payload),
+            Algorithm::Aes128Gcm => Aes128Gcm::new(GenericArray::from_slice(self.key_bytes()))
+                .decrypt(Nonce::from_slice(nonce), payload),
+            Algorithm::ChaCha20Poly1305 => {
+                ChaCha20Poly1305::new(key).decrypt(Nonce::from_slice(nonce), payload)
+            }
+            Algorithm::XChaCha20Poly1305 => {
+                XChaCha20Poly1305::new(key).decrypt(XNonce::from_slice(nonce), payload)
+            }
+        }
+        .map_err(|_| Error::AuthFailed)
+    }
+
+    /// Fill `buf` with a fresh random nonce of this session's width and return it.
+    fn random_nonce<'b>(
+        &self,
+        rng: &mut (impl RngCore + CryptoRng),
+        buf: &'b mut [u8; MAX_NONCE_LEN],
+    ) -> &'b [u8] {
+        let nonce = &mut buf[..self.algorithm.nonce_len()];
+        rng.fill_bytes(nonce);
+        nonce
+    }
+
+    /// Encrypt a payload with a random nonce (`Algorithm::nonce_len` bytes) prepended to
+    /// the ciphertext.
+    #[cfg(feature = "std")]
     pub fn seal(&self, aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, Error> {
-        let cipher = Aes256Gcm::new(&self.key);
-        let mut nonce_bytes = [0u8; 12];
-        OsRng.fill_bytes(&mut nonce_bytes);
-        let nonce = Nonce::from_slice(&nonce_bytes);
-        let mut out = Vec::with_capacity(12 + plaintext.len() + 16);
-        out.extend_from_slice(&nonce_bytes);
-        let ct = cipher
+        self.seal_with_rng(&mut OsRng, aad, plaintext)
+    }
+
+    /// `seal` drawing the nonce from `rng`, for `no_std` targets without an OS RNG (e.g. a
+    /// hardware TRNG wrapped as `RngCore`). Frames are identical to `seal`'s.
+    pub fn seal_with_rng(
+        &self,
+        rng: &mut (impl RngCore + CryptoRng),
+        aad: &[u8],
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let mut buf = [0u8; MAX_NONCE_LEN];
+        let nonce = self.random_nonce(rng, &mut buf);
+        self.seal_nonce(nonce, aad, plaintext)
+    }
+
+    /// `seal` with a caller-supplied 12-byte nonce, for known-answer tests and external
+    /// nonce schedulers. The nonce is still prepended, so `open` works unchanged.
+    ///
+    /// **Never seal two messages with the same nonce under one key.** With AES-GCM and
+    /// ChaCha20-Poly1305 a repeated nonce reveals the XOR of the plaintexts and lets an
+    /// attacker forge tags for any message under that key. Prefer `seal` (random nonces)
+    /// or `CounterSession` unless you can prove uniqueness.
+    ///
+    /// Fails with `Error::Encrypt` on an `XChaCha20Poly1305` session (24-byte nonces).
+    pub fn seal_with_nonce(
+        &self,
+        nonce: &[u8; 12],
+        aad: &[u8],
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        if self.algorithm.nonce_len() != nonce.len() {
+            return Err(Error::Encrypt);
+        }
+        self.seal_nonce(nonce, aad, plaintext)
+    }
+
+    /// `seal` with the nonce supplied by the caller.
+    fn seal_nonce(&self, nonce: &[u8], aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut out = Vec::with_capacity(nonce.len() + plaintext.len() + 16);
+        out.extend_from_slice(nonce);
+        let ct = self.encrypt(
+            nonce,
+            Payload {
+                msg: plaintext,
+                aad,
+            },
+        )?;
+        out.extend_from_slice(&ct);
+        Ok(out)
+    }
+
+    /// Decrypt a payload produced by `seal`; the nonce width comes from this session's
+    /// algorithm. A frame too short to hold the nonce and tag is `Error::Truncated`, one
+    /// whose tag doesn't verify is `Error::AuthFailed`.
+    pub fn open(&self, aad: &[u8], framed: &[u8]) -> Result<Vec<u8>, Error> {
+        let nonce_len = self.algorithm.nonce_len();
+        if framed.len() < nonce_len + TAG_LEN {
+            return Err(Error::Truncated);
+        }
+        let (nonce_bytes, ct) = framed.split_at(nonce_len);
+        self.decrypt(nonce_bytes, Payload { msg: ct, aad })
+    }
+
+    /// `seal` without allocating: `buf` holds the plaintext on entry and the frame
+    /// (nonce | ciphertext | tag) on return. Reusing one buffer across calls keeps a hot
+    /// loop allocation-free once its capacity has grown.
+    #[cfg(feature = "std")]
+    pub fn seal_in_place(&self, aad: &[u8], buf: &mut Vec<u8>) -> Result<(), Error> {
+        self.seal_in_place_with_rng(&mut OsRng, aad, buf)
+    }
+
+    /// `seal_in_place` drawing the nonce from `rng`; see `seal_with_rng`.
+    pub fn seal_in_place_with_rng(
+        &self,
+        rng: &mut (impl RngCore + CryptoRng),
+        aad: &[u8],
+        buf: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        let mut nonce_buf = [0u8; MAX_NONCE_LEN];
+        let nonce = self.random_nonce(rng, &mut nonce_buf);
+        let (n, len) = (nonce.len(), buf.len());
+        buf.reserve(n + TAG_LEN);
+        buf.resize(n + len, 0);
+        buf.copy_within(..len, n);
+        buf[..n].copy_from_slice(nonce);
+        let tag = self.encrypt_detached(nonce, aad, &mut buf[n..])?;
+        buf.extend_from_slice(&tag);
+        Ok(())
+    }
+
+    /// `open` without allocating: `buf` holds a `seal` frame on entry and is truncated to
+    /// the plaintext on success. On error its contents are unspecified.
+    pub fn open_in_place(&self, aad: &[u8], buf: &mut Vec<u8>) -> Result<(), Error> {
+        let n = self.algorithm.nonce_len();
+        if buf.len() < n + TAG_LEN {
+            return Err(Error::Truncated);
+        }
+        let body_end = buf.len() - TAG_LEN;
+        let (head, tag) = buf.split_at_mut(body_end);
+        let (nonce, body) = head.split_at_mut(n);
+        self.decrypt_detached(nonce, aad, body, tag)?;
+        buf.copy_within(n..body_end, 0);
+        buf.truncate(body_end - n);
+        Ok(())
+    }
+
+    /// Encrypt with a caller-supplied nonce, returning the ciphertext and the tag separately
+    /// for protocols that carry the tag in its own field. Nothing is prepended. The nonce
+    /// must be `Algorithm::nonce_len` bytes (`Error::Encrypt` otherwise) and, as with
+    /// `seal_with_nonce`, must never repeat under one key.
+    pub fn seal_detached(
+        &self,
+        nonce: &[u8],
+        aad: &[u8],
+        plaintext: &[u8],
+    ) -> Result<(Vec<u8>, [u8; TAG_LEN]), Error> {
+        if nonce.len() != self.algorithm.nonce_len() {
+            return Err(Error::Encrypt);
+        }
+        let mut ct = plaintext.to_vec();
+        let tag = self.encrypt_detached(nonce, aad, &mut ct)?;
+        Ok((ct, tag))
+    }
+
+    /// Decrypt the output of `seal_detached`. A wrong-length nonce is `Error::Decrypt`, a
+    /// tag that doesn't verify is `Error::AuthFailed`.
+    pub fn open_detached(
+        &self,
+        nonce: &[u8],
+        aad: &[u8],
+        ciphertext: &[u8],
+        tag: &[u8; TAG_LEN],
+    ) -> Result<Vec<u8>, Error> {
+        if nonce.len() != self.algorithm.nonce_len() {
+            return Err(Error::Decrypt);
+        }
+        let mut pt = ciphertext.to_vec();
+        self.decrypt_detached(nonce, aad, &mut pt, tag)?;
+        Ok(pt)
+    }
+
+    fn encrypt_detached(
+        &self,
+        nonce: &[u8],
+        aad: &[u8],
+        buf: &mut [u8],
+    ) -> Result<[u8; TAG_LEN], Error> {
+        #[cfg(feature = "ring-backend")]
+        if ring_backend::supports(self.algorithm) {
+            return ring_backend::encrypt_detached(
+                self.algorithm,
+                self.key_bytes(),
+                nonce,
+                aad,
+                buf,
+            );
+        }
+        let key = GenericArray::from_slice(&self.key[..]);
+        match self.algorithm {
+            Algorithm::Aes256Gcm => {
+                Aes256Gcm::new(key).encrypt_in_place_detached(Nonce::from_slice(nonce), aad, buf)
+            }
+            Algorithm::Aes128Gcm => Aes128Gcm::new(GenericArray::from_slice(self.key_bytes()))
+                .encrypt_in_place_detached(Nonce::from_slice(nonce), aad, buf),
+            Algorithm::ChaCha20Poly1305 => ChaCha20Poly1305::new(key).encrypt_in_place_detached(
+                Nonce::from_slice(nonce),
+                aad,
+                buf,
+            ),
+            Algorithm::XChaCha20Poly1305 => XChaCha20Poly1305::new(key).encrypt_in_place_detached(
+                XNonce::from_slice(nonce),
+                aad,
+                buf,
+            ),
+        }
+        .map(Into::into)
+        .map_err(|_| Error::Encrypt)
+    }
+
+    fn decrypt_detached(
+        &self,
+        nonce: &[u8],
+        aad: &[u8],
+        buf: &mut [u8],
+        tag: &[u8],
+    ) -> Result<(), Error> {
+        #[cfg(feature = "ring-backend")]
+        if ring_backend::supports(self.algorithm) {
+            return ring_backend::decrypt_detached(
+                self.algorithm,
+                self.key_bytes(),
+                nonce,
+                aad,
+                buf,
+                tag,
+            );
+        }
+        let key = GenericArray::from_slice(&self.key[..]);
+        let tag = GenericArray::from_slice(tag);
+        match self.algorithm {
+            Algorithm::Aes256Gcm => Aes256Gcm::new(key).decrypt_in_place_detached(
+                Nonce::from_slice(nonce),
+                aad,
+                buf,
+                tag,
+            ),
+            Algorithm::Aes128Gcm => Aes128Gcm::new(GenericArray::from_slice(self.key_bytes()))
+                .decrypt_in_place_detached(Nonce::from_slice(nonce), aad, buf, tag),
+            Algorithm::ChaCha20Poly1305 => ChaCha20Poly1305::new(key).decrypt_in_place_detached(
+                Nonce::from_slice(nonce),
+                aad,
+                buf,
+                tag,
+            ),
+            Algorithm::XChaCha20Poly1305 => XChaCha20Poly1305::new(key).decrypt_in_place_detached(
+                XNonce::from_slice(nonce),
+                aad,
+                buf,
+                tag,
+            ),
+        }
+        .map_err(|_| Error::AuthFailed)
+    }
+
+    /// Like `open`, but also reports how the frame was protected, for auditing and routing.
+    ///
+    /// Frames don't carry an algorithm or key-id header yet, so those come from this session
+    /// (the ones that just authenticated the frame); the nonce is read from the frame itself.
+    pub fn open_with_meta(&self, aad: &[u8], framed: &[u8]) -> Result<(FrameMeta, Vec<u8>), Error> {
+        let plaintext = self.open(aad, framed)?;
+        let meta = FrameMeta {
+            algorithm: self.algorithm,
+            key_id: self.key_id(),
+            nonce: framed[..self.algorithm.nonce_len()].to_vec(),
+        };
+        Ok((meta, plaintext))
+    }
+
+    /// `seal` behind a self-describing header, so the format can evolve:
+    /// `b"AE2E"` | version (1) | `Algorithm::id` | nonce | ciphertext+tag.
+    /// The header is bound into the AAD.
+    #[cfg(feature = "std")]
+    pub fn seal_framed(&self, aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut out =
+            Vec::with_capacity(FRAME_HEADER_LEN + MAX_NONCE_LEN + plaintext.len() + TAG_LEN);
+        out.extend_from_slice(FRAME_MAGIC);
+        out.push(FRAME_VERSION);
+        out.push(self.algorithm.id());
+        out.extend(self.seal(&bind_aad(&[&out, aad]), plaintext)?);
+        Ok(out)
+    }
+
+    /// Open a `seal_framed` frame. A missing magic is `Error::InvalidHeader`, a version
+    /// other than 1 is `Error::UnsupportedVersion`, and an algorithm id that isn't this
+    /// session's is `Error::UnknownAlgorithm` (unknown id) or `Error::InvalidHeader`.
+    pub fn open_framed(&self, aad: &[u8], framed: &[u8]) -> Result<Vec<u8>, Error> {
+        if framed.len() < FRAME_HEADER_LEN || &framed[..4] != FRAME_MAGIC {
+            return Err(Error::InvalidHeader);
+        }
+        let (header, sealed) = framed.split_at(FRAME_HEADER_LEN);
+        if header[4] != FRAME_VERSION {
+            return Err(Error::UnsupportedVersion(header[4]));
+        }
+        if Algorithm::from_id(header[5])? != self.algorithm {
+            return Err(Error::InvalidHeader);
+        }
+        self.open(&bind_aad(&[header, aad]), sealed)
+    }
+
+    /// Like `seal`, but returns the frame as standard base64 text.
+    #[cfg(feature = "base64")]
+    pub fn seal_base64(&self, aad: &[u8], plaintext: &[u8]) -> Result<String, Error> {
+        use base64::Engine;
+        let framed = self.seal(aad, plaintext)?;
+        Ok(base64::engine::general_purpose::STANDARD.encode(framed))
+    }
+
+    /// Decrypt a base64 frame produced by `seal_base64`.
+    #[cfg(feature = "base64")]
+    pub fn open_base64(&self, aad: &[u8], s: &str) -> Result<Vec<u8>, Error> {
+        use base64::Engine;
+        let framed = base64::engine::general_purpose::STANDARD
+            .decode(s)
+            .map_err(|_| Error::Base64)?;
+        self.open(aad, &framed)
+    }
+}
+
+impl Session {
+    /// Encrypt a 32-byte key under this session for distribution.
+    #[cfg(feature = "std")]
+    pub fn wrap_key(&self, key: &[u8; 32]) -> Vec<u8> {
+        self.seal(KEY_WRAP_AAD, key)
+            .expect("AEAD sealing a 32-byte key cannot fail")
+    }
+
+    /// Recover a key produced by `wrap_key`; the result is wiped from memory on drop.
+    pub fn unwrap_key(&self, wrapped: &[u8]) -> Result<Zeroizing<[u8; 32]>, Error> {
+        let plain = Zeroizing::new(self.open(KEY_WRAP_AAD, wrapped)?);
+        let mut key = Zeroizing::new([0u8; 32]);
+        if plain.len() != key.len() {
+            return Err(Error::InvalidKey);
+        }
+        key.copy_from_slice(&plain);
+        Ok(key)
+    }
+
+    /// Authenticate `data` without encrypting it (e.g. presence beacons).
+    /// Frame: nonce | data in the clear | 16-byte tag.
+    #[cfg(feature = "std")]
+    pub fn seal_public(&self, data: &[u8]) -> Vec<u8> {
+        let mut buf = [0u8; MAX_NONCE_LEN];
+        let nonce = self.random_nonce(&mut OsRng, &mut buf);
+        let aad = bind_aad(&[BEACON_LABEL, data]);
+        let tag = self
             .encrypt(
                 nonce,
-                aes_gcm::aead::Payload {
-                    msg: plaintext,
-                    aad,
+                Payload {
+                    msg: &[],
+                    aad: &aad,
                 },
             )
-            .map_err(|_| Error::Encrypt)?;
+            .expect("AEAD over an empty message cannot fail");
+        let mut out = Vec::with_capacity(nonce.len() + data.len() + tag.len());
+        out.extend_from_slice(nonce);
+        out.extend_from_slice(data);
+        out.extend_from_slice(&tag);
+        out
+    }
+
+    /// Verify a beacon from `seal_public` and return its cleartext data.
+    pub fn open_public(&self, framed: &[u8]) -> Result<Vec<u8>, Error> {
+        let nonce_len = self.algorithm.nonce_len();
+        if framed.len() < nonce_len + 16 {
+            return Err(Error::Truncated);
+        }
+        let (nonce, rest) = framed.split_at(nonce_len);
+        let (data, tag) = rest.split_at(rest.len() - 16);
+        let aad = bind_aad(&[BEACON_LABEL, data]);
+        self.decrypt(
+            nonce,
+            Payload {
+                msg: tag,
+                aad: &aad,
+            },
+        )?;
+        Ok(data.to_vec())
+    }
+
+    /// Seal with the key-id carried encrypted, so observers can't tell which key (sender) a
+    /// frame belongs to. Frame: nonce | 8-byte masked key-id | ciphertext+tag.
+    ///
+    /// The key-id is XORed with a keyed BLAKE3 hash of the nonce, so each frame shows a fresh
+    /// value; a recipient holding several sessions finds the right one with
+    /// `is_sealed_sender_for`. The masked key-id is also bound into the AAD.
+    #[cfg(feature = "std")]
+    pub fn seal_sealed_sender(&self, aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut buf = [0u8; MAX_NONCE_LEN];
+        let nonce = self.random_nonce(&mut OsRng, &mut buf);
+        let masked = self.masked_key_id(nonce);
+        let ct = self.encrypt(
+            nonce,
+            Payload {
+                msg: plaintext,
+                aad: &bind_aad(&[&masked, aad]),
+            },
+        )?;
+        let mut out = Vec::with_capacity(nonce.len() + 8 + ct.len());
+        out.extend_from_slice(nonce);
+        out.extend_from_slice(&masked);
         out.extend_from_slice(&ct);
         Ok(out)
     }
 
-    /// Decrypt a payload produced by `seal`.
-    pub fn open(&self, aad: &[u8], framed: &[u8]) -> Result<Vec<u8>, Error> {
-        if framed.len() < 12 {
+    /// Whether a `seal_sealed_sender` frame was sealed under this session's key.
+    pub fn is_sealed_sender_for(&self, framed: &[u8]) -> bool {
+        let nonce_len = self.algorithm.nonce_len();
+        framed.len() >= nonce_len + 8 && {
+            let (nonce, rest) = framed.split_at(nonce_len);
+            constant_time_eq(&rest[..8], &self.masked_key_id(nonce))
+        }
+    }
+
+    /// Decrypt a frame produced by `seal_sealed_sender`.
+    pub fn open_sealed_sender(&self, aad: &[u8], framed: &[u8]) -> Result<Vec<u8>, Error> {
+        if !self.is_sealed_sender_for(framed) {
             return Err(Error::Decrypt);
         }
-        let (nonce_bytes, ct) = framed.split_at(12);
-        let cipher = Aes256Gcm::new(&self.key);
-        let nonce = Nonce::from_slice(nonce_bytes);
-        cipher
-            .decrypt(nonce, aes_gcm::aead::Payload { msg: ct, aad })
-            .map_err(|_| Error::Decrypt)
+        let (nonce, rest) = framed.split_at(self.algorithm.nonce_len());
+        let (masked, ct) = rest.split_at(8);
+        self.decrypt(
+            nonce,
+            Payload {
+                msg: ct,
+                aad: &bind_aad(&[masked, aad]),
+            },
+        )
+    }
+
+    fn masked_key_id(&self, nonce: &[u8]) -> [u8; 8] {
+        let mask_key = blake3::derive_key("ads-e2ee 2026 sealed sender", self.key_bytes());
+        let mask = blake3::keyed_hash(&mask_key, nonce);
+        let mut id = self.key_id();
+        for (b, m) in id.iter_mut().zip(mask.as_bytes()) {
+            *b ^= m;
+        }
+        id
+    }
+
+    /// Short code for comparing keys out of band, e.g. read aloud between operators.
+    /// 8 base32 characters (40 bits) of a BLAKE3 subkey, so the raw key isn't exposed.
+    pub fn fingerprint(&self) -> String {
+        const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+        let sub = blake3::derive_key("ads-e2ee 2026 fingerprint", self.key_bytes());
+        let bits = sub[..5]
+            .iter()
+            .fold(0u64, |acc, b| (acc << 8) | u64::from(*b));
+        (0..8)
+            .rev()
+            .map(|i| ALPHABET[((bits >> (i * 5)) & 0x1F) as usize] as char)
+            .collect()
+    }
+
+    /// Non-secret 8-byte identifier of the key for logs and routing, e.g. to record which
+    /// key epoch opened a frame. Truncated from a BLAKE3 subkey, so it reveals nothing
+    /// useful about the key bytes.
+    pub fn key_id(&self) -> [u8; 8] {
+        let sub = blake3::derive_key("ads-e2ee 2026 key id", self.key_bytes());
+        let mut id = [0u8; 8];
+        id.copy_from_slice(&sub[..8]);
+        id
+    }
+
+    /// Start a frame chain keyed by a subkey derived from this session's key.
+    pub fn frame_chain(&self) -> FrameChain {
+        FrameChain::new(blake3::derive_key(
+            "ads-e2ee 2026 frame chain",
+            self.key_bytes(),
+        ))
+    }
+}
+
+/// Running keyed BLAKE3 hash over a sequence of frames.
+///
+/// The sender calls `link` on each sealed frame and transmits the returned tag with it; the
+/// receiver calls `verify` on each frame it gets. A dropped, injected or reordered frame makes
+/// the two chains diverge and `verify` fails with `Error::ChainBroken`.
+///
+/// The key is wiped when the chain (or any clone of it) is dropped.
+#[derive(Clone)]
+pub struct FrameChain {
+    key: Zeroizing<[u8; 32]>,
+    state: blake3::Hash,
+}
+
+impl ZeroizeOnDrop for FrameChain {}
+
+impl FrameChain {
+    pub fn new(mut key: [u8; 32]) -> Self {
+        let stored = Zeroizing::new(key);
+        key.zeroize();
+        Self {
+            key: stored,
+            state: blake3::Hash::from_bytes([0u8; 32]),
+        }
+    }
+
+    fn next(&self, frame: &[u8]) -> blake3::Hash {
+        let mut h = blake3::Hasher::new_keyed(&self.key);
+        h.update(self.state.as_bytes());
+        h.update(frame);
+        h.finalize()
+    }
+
+    /// Fold `frame` into the chain and return the tag to send alongside it.
+    pub fn link(&mut self, frame: &[u8]) -> [u8; 32] {
+        self.state = self.next(frame);
+        *self.state.as_bytes()
+    }
+
+    /// Check a received frame's tag and advance the chain; the chain is left untouched on error.
+    pub fn verify(&mut self, frame: &[u8], tag: &[u8; 32]) -> Result<(), Error> {
+        let next = self.next(frame);
+        // blake3::Hash equality is constant-time.
+        if next != blake3::Hash::from_bytes(*tag) {
+            return Err(Error::ChainBroken);
+        }
+        self.state = next;
+        Ok(())
     }
 }
 
-/// Derive a session from a pre-shared key (placeholder for initial prototypes).
-pub fn session_from_psk(psk: &[u8]) -> Session {
-    use blake3::hash as blake3_hash;
-    let mut key = [0u8; 32];
-    key.copy_from_slice(blake3_hash(psk).as_bytes());
-    Session::from_key(key)
+/// Session that derives nonces from a message counter instead of the RNG, so nonces are
+/// unique by construction for the life of the session.
+///
+/// Nonce: random prefix fixed at construction (4 bytes for 12-byte nonces) | u64 counter,
+/// big-endian. Frames are ordinary `seal` frames, so the peer opens them with `Session::open`.
+/// Counters are not persisted: build a new `CounterSession` (fresh prefix) after a restart.
+/// Needs 64-bit atomics, so it is unavailable on targets without them.
+#[cfg(target_has_atomic = "64")]
+pub struct CounterSession {
+    session: Session,
+    prefix: [u8; MAX_NONCE_LEN - 8],
+    counter: core::sync::atomic::AtomicU64,
 }
 
-#[cfg(feature = "rustls")]
-pub mod tls {
-    //! Hook points to derive an application-level session key via a rustls TLS 1.3 handshake.
-    //! Integrate by exporting keying material (EKM) after handshake and feeding it to `Session::from_key`.
-    use super::Session;
-    use rustls::Connection;
-
-    pub fn session_from_ekm(conn: &Connection) -> Option<Session> {
-        // Export 32 bytes of keying material following RFC 5705-like interface (rustls API provides EKM).
-        let mut out = [0u8; 32];
-        let label = b"ads-e2ee-2026";
-        let context: &[u8] = &[];
-        conn.export_keying_material(&mut out, label, Some(context))
-            .ok()?;
-        Some(Session::from_key(out))
+#[cfg(target_has_atomic = "64")]
+impl CounterSession {
+    #[cfg(feature = "std")]
+    pub fn new(session: Session) -> Self {
+        Self::from_rng(session, &mut OsRng)
+    }
+
+    /// `new` with the nonce prefix drawn from `rng`.
+    pub fn from_rng(session: Session, rng: &mut (impl RngCore + CryptoRng)) -> Self {
+        let mut prefix = [0u8; MAX_NONCE_LEN - 8];
+        rng.fill_bytes(&mut prefix);
+        Self {
+            session,
+            prefix,
+            counter: core::sync::atomic::AtomicU64::new(0),
+        }
+    }
+
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    /// Seal with the next counter nonce; fails with `Error::NonceExhausted` rather than wrap.
+    pub fn seal(&self, aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+        use core::sync::atomic::Ordering;
+        let counter = self
+            .counter
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |c| c.checked_add(1))
+            .map_err(|_| Error::NonceExhausted)?;
+        let prefix_len = self.session.algorithm.nonce_len() - 8;
+        let mut nonce = [0u8; MAX_NONCE_LEN];
+        nonce[..prefix_len].copy_from_slice(&self.prefix[..prefix_len]);
+        nonce[prefix_len..prefix_len + 8].copy_from_slice(&counter.to_be_bytes());
+        self.session
+            .seal_nonce(&nonce[..prefix_len + 8], aad, plaintext)
+    }
+
+    pub fn open(&self, aad: &[u8], framed: &[u8]) -> Result<Vec<u8>, Error> {
+        self.session.open(aad, framed)
+    }
+}
+
+/// Nonce bytes STREAM (`aead::stream` BE32) reserves for the chunk counter and the
+/// last-chunk flag; the rest is the per-stream prefix.
+const STREAM_OVERHEAD: usize = 5;
+
+enum StreamSealer {
+    Aes256Gcm(Box<EncryptorBE32<Aes256Gcm>>),
+    Aes128Gcm(Box<EncryptorBE32<Aes128Gcm>>),
+    ChaCha20Poly1305(EncryptorBE32<ChaCha20Poly1305>),
+    XChaCha20Poly1305(EncryptorBE32<XChaCha20Poly1305>),
+}
+
+enum StreamOpener {
+    Aes256Gcm(Box<DecryptorBE32<Aes256Gcm>>),
+    Aes128Gcm(Box<DecryptorBE32<Aes128Gcm>>),
+    ChaCha20Poly1305(DecryptorBE32<ChaCha20Poly1305>),
+    XChaCha20Poly1305(DecryptorBE32<XChaCha20Poly1305>),
+}
+
+/// Incremental encryption of a payload too large to hold in one buffer, using the STREAM
+/// construction: each chunk is its own AEAD frame (ciphertext | tag) under a nonce built
+/// from a random per-stream prefix, a chunk counter and a last-chunk flag.
+///
+/// Send `nonce_prefix` first, then each `push` frame, then the `finish` frame; the
+/// transport must preserve frame boundaries. Dropped, reordered or truncated streams fail
+/// to open in `OpenStream`.
+pub struct SealStream {
+    inner: StreamSealer,
+    prefix: Vec<u8>,
+    aad: Vec<u8>,
+}
+
+impl SealStream {
+    /// Start a stream under `session`, authenticating `aad` with every chunk.
+    #[cfg(feature = "std")]
+    pub fn new(session: &Session, aad: &[u8]) -> Self {
+        Self::from_rng(session, &mut OsRng, aad)
+    }
+
+    /// `new` with the nonce prefix drawn from `rng`.
+    pub fn from_rng(session: &Session, rng: &mut (impl RngCore + CryptoRng), aad: &[u8]) -> Self {
+        let mut prefix = vec![0u8; session.algorithm.nonce_len() - STREAM_OVERHEAD];
+        rng.fill_bytes(&mut prefix);
+        let key = GenericArray::from_slice(&session.key[..]);
+        let nonce = prefix.as_slice();
+        let inner = match session.algorithm {
+            Algorithm::Aes256Gcm => StreamSealer::Aes256Gcm(Box::new(EncryptorBE32::from_aead(
+                Aes256Gcm::new(key),
+                GenericArray::from_slice(nonce),
+            ))),
+            Algorithm::Aes128Gcm => StreamSealer::Aes128Gcm(Box::new(EncryptorBE32::from_aead(
+                Aes128Gcm::new(GenericArray::from_slice(session.key_bytes())),
+                GenericArray::from_slice(nonce),
+            ))),
+            Algorithm::ChaCha20Poly1305 => {
+                StreamSealer::ChaCha20Poly1305(EncryptorBE32::from_aead(
+                    ChaCha20Poly1305::new(key),
+                    GenericArray::from_slice(nonce),
+                ))
+            }
+            Algorithm::XChaCha20Poly1305 => {
+                StreamSealer::XChaCha20Poly1305(EncryptorBE32::from_aead(
+                    XChaCha20Poly1305::new(key),
+                    GenericArray::from_slice(nonce),
+                ))
+            }
+        };
+        Self {
+            inner,
+            prefix,
+            aad: aad.to_vec(),
+        }
+    }
+
+    /// Per-stream nonce prefix the receiver needs for `OpenStream::new`.
+    pub fn nonce_prefix(&self) -> &[u8] {
+        &self.prefix
+    }
+
+    /// Seal the next chunk. Fails with `Error::NonceExhausted` after 2^32 - 1 chunks.
+    pub fn push(&mut self, chunk: &[u8]) -> Result<Vec<u8>, Error> {
+        let payload = Payload {
+            msg: chunk,
+            aad: &self.aad,
+        };
+        match &mut self.inner {
+            StreamSealer::Aes256Gcm(s) => s.encrypt_next(payload),
+            StreamSealer::Aes128Gcm(s) => s.encrypt_next(payload),
+            StreamSealer::ChaCha20Poly1305(s) => s.encrypt_next(payload),
+            StreamSealer::XChaCha20Poly1305(s) => s.encrypt_next(payload),
+        }
+        .map_err(|_| Error::NonceExhausted)
+    }
+
+    /// End the stream, returning the final (empty, tag-only) frame that marks it complete.
+    pub fn finish(self) -> Result<Vec<u8>, Error> {
+        let payload = Payload {
+            msg: &[],
+            aad: &self.aad,
+        };
+        match self.inner {
+            StreamSealer::Aes256Gcm(s) => s.encrypt_last(payload),
+            StreamSealer::Aes128Gcm(s) => s.encrypt_last(payload),
+            StreamSealer::ChaCha20Poly1305(s) => s.encrypt_last(payload),
+            StreamSealer::XChaCha20Poly1305(s) => s.encrypt_last(payload),
+        }
+        .map_err(|_| Error::Encrypt)
+    }
+}
+
+/// Receiving side of `SealStream`.
+pub struct OpenStream {
+    inner: StreamOpener,
+    aad: Vec<u8>,
+}
+
+impl OpenStream {
+    /// Start opening a stream whose sender reported `nonce_prefix`.
+    pub fn new(session: &Session, nonce_prefix: &[u8], aad: &[u8]) -> Result<Self, Error> {
+        if nonce_prefix.len() != session.algorithm.nonce_len() - STREAM_OVERHEAD {
+            return Err(Error::Truncated);
+        }
+        let key = GenericArray::from_slice(&session.key[..]);
+        let inner = match session.algorithm {
+            Algorithm::Aes256Gcm => StreamOpener::Aes256Gcm(Box::new(DecryptorBE32::from_aead(
+                Aes256Gcm::new(key),
+                GenericArray::from_slice(nonce_prefix),
+            ))),
+            Algorithm::Aes128Gcm => StreamOpener::Aes128Gcm(Box::new(DecryptorBE32::from_aead(
+                Aes128Gcm::new(GenericArray::from_slice(session.key_bytes())),
+                GenericArray::from_slice(nonce_prefix),
+            ))),
+            Algorithm::ChaCha20Poly1305 => {
+                StreamOpener::ChaCha20Poly1305(DecryptorBE32::from_aead(
+                    ChaCha20Poly1305::new(key),
+                    GenericArray::from_slice(nonce_prefix),
+                ))
+            }
+            Algorithm::XChaCha20Poly1305 => {
+                StreamOpener::XChaCha20Poly1305(DecryptorBE32::from_aead(
+                    XChaCha20Poly1305::new(key),
+                    GenericArray::from_slice(nonce_prefix),
+                ))
+            }
+        };
+        Ok(Self {
+            inner,
+            aad: aad.to_vec(),
+        })
+    }
+
+    /// Open the next `SealStream::push` frame.
+    pub fn push(&mut self, frame: &[u8]) -> Result<Vec<u8>, Error> {
+        let payload = Payload {
+            msg: frame,
+            aad: &self.aad,
+        };
+        match &mut self.inner {
+            StreamOpener::Aes256Gcm(s) => s.decrypt_next(payload),
+            StreamOpener::Aes128Gcm(s) => s.decrypt_next(payload),
+            StreamOpener::ChaCha20Poly1305(s) => s.decrypt_next(payload),
+            StreamOpener::XChaCha20Poly1305(s) => s.decrypt_next(payload),
+        }
+        .map_err(|_| Error::AuthFailed)
+    }
+
+    /// Verify the `SealStream::finish` frame; only then is the stream known to be complete.
+    pub fn finish(self, frame: &[u8]) -> Result<(), Error> {
+        let payload = Payload {
+            msg: frame,
+            aad: &self.aad,
+        };
+        match self.inner {
+            StreamOpener::Aes256Gcm(s) => s.decrypt_last(payload),
+            StreamOpener::Aes128Gcm(s) => s.decrypt_last(payload),
+            StreamOpener::ChaCha20Poly1305(s) => s.decrypt_last(payload),
+            StreamOpener::XChaCha20Poly1305(s) => s.decrypt_last(payload),
+        }
+        .map(|_| ())
+        .map_err(|_| Error::AuthFailed)
+    }
+}
+
+#[cfg(feature = "compress")]
+impl Session {
+    const RAW: u8 = 0;
+    const ZSTD: u8 = 1;
+
+    /// Seal after zstd-compressing `plaintext` at `level`, falling back to the raw bytes
+    /// when compression doesn't help. A 1-byte flag ahead of the payload (inside the
+    /// ciphertext) records which was used.
+    pub fn seal_compressed(
+        &self,
+        aad: &[u8],
+        plaintext: &[u8],
+        level: i32,
+    ) -> Result<Vec<u8>, Error> {
+        let compressed = zstd::bulk::compress(plaintext, level).map_err(|_| Error::Encrypt)?;
+        let (flag, body) = if compressed.len() < plaintext.len() {
+            (Self::ZSTD, compressed.as_slice())
+        } else {
+            (Self::RAW, plaintext)
+        };
+        let mut buf = Vec::with_capacity(1 + body.len());
+        buf.push(flag);
+        buf.extend_from_slice(body);
+        self.seal(aad, &buf)
+    }
+
+    /// Open a `seal_compressed` frame, refusing to produce more than `max_len` bytes
+    /// (`Error::DecompressLimit`) so a small frame can't expand into a huge allocation.
+    pub fn open_compressed(
+        &self,
+        aad: &[u8],
+        framed: &[u8],
+        max_len: usize,
+    ) -> Result<Vec<u8>, Error> {
+        use std::io::Read;
+        let buf = self.open(aad, framed)?;
+        let (&flag, body) = buf.split_first().ok_or(Error::Decompress)?;
+        let out = match flag {
+            Self::RAW => body.to_vec(),
+            Self::ZSTD => {
+                let mut out = Vec::new();
+                zstd::stream::read::Decoder::new(body)
+                    .map_err(|_| Error::Decompress)?
+                    .take(max_len as u64 + 1)
+                    .read_to_end(&mut out)
+                    .map_err(|_| Error::Decompress)?;
+                out
+            }
+            _ => return Err(Error::Decompress),
+        };
+        if out.len() > max_len {
+            return Err(Error::DecompressLimit(max_len));
+        }
+        Ok(out)
+    }
+}
+
+/// Sliding-window replay filter over 64-bit sequence numbers.
+///
+/// Tracks the highest sequence seen and a 64-entry bitmap behind it, so mildly reordered
+/// datagrams are accepted once each while duplicates and anything older than the window
+/// are rejected.
+#[derive(Debug, Clone, Default)]
+pub struct ReplayWindow {
+    highest: Option<u64>,
+    /// Bit `i` set means `highest - i` has been seen.
+    seen: u64,
+}
+
+impl ReplayWindow {
+    pub const SIZE: u64 = 64;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether `seq` would be accepted, without recording it.
+    pub fn check(&self, seq: u64) -> bool {
+        match self.highest {
+            None => true,
+            Some(highest) if seq > highest => true,
+            Some(highest) => {
+                let age = highest - seq;
+                age < Self::SIZE && self.seen & (1 << age) == 0
+            }
+        }
+    }
+
+    /// Accept and record `seq`, or return `false` for a replay or a too-old sequence.
+    pub fn check_and_update(&mut self, seq: u64) -> bool {
+        if !self.check(seq) {
+            return false;
+        }
+        match self.highest {
+            Some(highest) if seq <= highest => self.seen |= 1 << (highest - seq),
+            Some(highest) => {
+                let shift = seq - highest;
+                self.seen = if shift < Self::SIZE {
+                    (self.seen << shift) | 1
+                } else {
+                    1
+                };
+                self.highest = Some(seq);
+            }
+            None => {
+                self.seen = 1;
+                self.highest = Some(seq);
+            }
+        }
+        true
+    }
+}
+
+impl Session {
+    /// Seal with a sequence number for replay protection.
+    /// Frame: u64 big-endian `seq` | `seal` output, with `seq` bound into the AAD.
+    #[cfg(feature = "std")]
+    pub fn seal_seq(&self, seq: u64, aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+        let seq = seq.to_be_bytes();
+        let sealed = self.seal(&bind_aad(&[&seq, aad]), plaintext)?;
+        let mut out = Vec::with_capacity(seq.len() + sealed.len());
+        out.extend_from_slice(&seq);
+        out.extend(sealed);
+        Ok(out)
+    }
+
+    /// Open a `seal_seq` frame, rejecting sequences `window` has already accepted (or that
+    /// fell behind it) with `Error::Replay`. The window only advances for authentic frames.
+    pub fn open_seq(
+        &self,
+        window: &mut ReplayWindow,
+        aad: &[u8],
+        framed: &[u8],
+    ) -> Result<(u64, Vec<u8>), Error> {
+        if framed.len() < 8 {
+            return Err(Error::Truncated);
+        }
+        let (seq_bytes, sealed) = framed.split_at(8);
+        let seq = u64::from_be_bytes(seq_bytes.try_into().expect("split at 8"));
+        if !window.check(seq) {
+            return Err(Error::Replay(seq));
+        }
+        let plaintext = self.open(&bind_aad(&[seq_bytes, aad]), sealed)?;
+        window.check_and_update(seq);
+        Ok((seq, plaintext))
+    }
+
+    /// Seal with a cleartext header that is also the AAD: u64 big-endian `ts` | u32
+    /// big-endian plaintext length, followed by the `seal` output. Altering either field,
+    /// or cutting the frame short, makes `open_with_header` fail. `ts` is in whatever unit
+    /// the caller's clock uses.
+    #[cfg(feature = "std")]
+    pub fn seal_with_header(&self, ts: u64, plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+        let len = u32::try_from(plaintext.len()).map_err(|_| Error::Encrypt)?;
+        let mut out = Vec::with_capacity(HEADER_LEN + MAX_NONCE_LEN + plaintext.len() + TAG_LEN);
+        out.extend_from_slice(&ts.to_be_bytes());
+        out.extend_from_slice(&len.to_be_bytes());
+        out.extend(self.seal(&out, plaintext)?);
+        Ok(out)
+    }
+
+    /// Open a `seal_with_header` frame, returning its timestamp and plaintext. With
+    /// `max_age`, a frame whose timestamp is more than `max_age` before `now` (same unit
+    /// as `ts`) is rejected with `Error::Expired` before decrypting.
+    pub fn open_with_header(
+        &self,
+        framed: &[u8],
+        now: u64,
+        max_age: Option<u64>,
+    ) -> Result<(u64, Vec<u8>), Error> {
+        if framed.len() < HEADER_LEN {
+            return Err(Error::Truncated);
+        }
+        let (header, sealed) = framed.split_at(HEADER_LEN);
+        let ts = u64::from_be_bytes(header[..8].try_into().expect("8 bytes"));
+        if max_age.is_some_and(|max| now.saturating_sub(ts) > max) {
+            return Err(Error::Expired(ts));
+        }
+        let plaintext = self.open(header, sealed)?;
+        Ok((ts, plaintext))
+    }
+}
+
+/// Forward-secret wrapper that replaces the key after every message, so a compromised
+/// current key doesn't expose earlier traffic.
+///
+/// Frame: u64 big-endian message index | `seal` output, with the index bound into the AAD.
+/// Key `i + 1` is HKDF-SHA256 of key `i`; the old key is wiped once it has been used. The
+/// receiver advances in lockstep and can skip ahead up to `max_skip` keys to get past
+/// dropped datagrams. A late frame whose key was already discarded can't be opened.
+pub struct Ratchet {
+    session: Session,
+    index: u64,
+    max_skip: u64,
+}
+
+impl Ratchet {
+    /// Start both ends from the same initial session.
+    pub fn new(session: Session, max_skip: u64) -> Self {
+        Self {
+            session,
+            index: 0,
+            max_skip,
+        }
+    }
+
+    /// Index of the next message to be sealed, or expected next when opening.
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+
+    fn next_session(session: &Session) -> Session {
+        let hk = hkdf::Hkdf::<sha2::Sha256>::new(None, session.key_bytes());
+        let mut key = Zeroizing::new([0u8; 32]);
+        hk.expand(b"ads-e2ee ratchet", &mut *key)
+            .expect("32 bytes is a valid HKDF-SHA256 output length");
+        Session::from_key_with(session.algorithm, *key)
+    }
+
+    /// Seal under the current key, then advance.
+    #[cfg(feature = "std")]
+    pub fn seal(&mut self, aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+        let index = self.index.to_be_bytes();
+        let sealed = self.session.seal(&bind_aad(&[&index, aad]), plaintext)?;
+        self.session = Self::next_session(&self.session);
+        self.index += 1;
+        let mut out = Vec::with_capacity(index.len() + sealed.len());
+        out.extend_from_slice(&index);
+        out.extend(sealed);
+        Ok(out)
+    }
+
+    /// Open the next frame, skipping the keys of up to `max_skip` missing ones. Frames
+    /// behind the current index are `Error::Replay`, frames too far ahead
+    /// `Error::RatchetSkip`; neither, nor a failed open, moves the ratchet.
+    pub fn open(&mut self, aad: &[u8], framed: &[u8]) -> Result<Vec<u8>, Error> {
+        if framed.len() < 8 {
+            return Err(Error::Truncated);
+        }
+        let (index_bytes, sealed) = framed.split_at(8);
+        let index = u64::from_be_bytes(index_bytes.try_into().expect("split at 8"));
+        let skip = index.checked_sub(self.index).ok_or(Error::Replay(index))?;
+        if skip > self.max_skip {
+            return Err(Error::RatchetSkip(index));
+        }
+        let mut session = self.session.clone();
+        for _ in 0..skip {
+            session = Self::next_session(&session);
+        }
+        let plaintext = session.open(&bind_aad(&[index_bytes, aad]), sealed)?;
+        self.session = Self::next_session(&session);
+        self.index = index + 1;
+        Ok(plaintext)
+    }
+}
+
+/// Sessions indexed by a key epoch, for rotating PSKs without out-of-band signaling.
+///
+/// Frames are `epoch byte | seal output`; the receiver picks the session from the leading
+/// byte. Tampering with the epoch just selects a different key, so the frame fails to open.
+#[derive(Clone, Default)]
+pub struct KeyRing {
+    sessions: alloc::collections::BTreeMap<u8, Session>,
+}
+
+impl KeyRing {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add or replace the session for `epoch`.
+    pub fn insert(&mut self, epoch: u8, session: Session) {
+        self.sessions.insert(epoch, session);
+    }
+
+    /// Drop a retired epoch; returns its session if it was present.
+    pub fn remove(&mut self, epoch: u8) -> Option<Session> {
+        self.sessions.remove(&epoch)
+    }
+
+    pub fn get(&self, epoch: u8) -> Option<&Session> {
+        self.sessions.get(&epoch)
+    }
+
+    /// Seal under the session for `epoch`, prepending the epoch byte.
+    #[cfg(feature = "std")]
+    pub fn seal_epoch(&self, epoch: u8, aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+        let session = self.get(epoch).ok_or(Error::UnknownEpoch(epoch))?;
+        let sealed = session.seal(aad, plaintext)?;
+        let mut out = Vec::with_capacity(1 + sealed.len());
+        out.push(epoch);
+        out.extend(sealed);
+        Ok(out)
+    }
+
+    /// Open a `seal_epoch` frame with the session its leading byte names.
+    pub fn open_any(&self, aad: &[u8], framed: &[u8]) -> Result<Vec<u8>, Error> {
+        let (&epoch, sealed) = framed.split_first().ok_or(Error::Truncated)?;
+        let session = self.get(epoch).ok_or(Error::UnknownEpoch(epoch))?;
+        session.open(aad, sealed)
+    }
+}
+
+impl Session {
+    /// Derive a session the way a TLS 1.3 peer computes an exporter (RFC 8446 §7.5) from the
+    /// connection's `exporter_master_secret` with SHA-256:
+    /// `HKDF-Expand-Label(Derive-Secret(secret, label, ""), "exporter", Hash(context), 32)`.
+    pub fn from_tls_exporter(secret: &[u8], label: &str, context: &[u8]) -> Session {
+        use sha2::{Digest, Sha256};
+        let mut derived = Zeroizing::new([0u8; 32]);
+        hkdf_expand_label(secret, label.as_bytes(), &Sha256::digest([]), &mut *derived);
+        let mut key = Zeroizing::new([0u8; 32]);
+        hkdf_expand_label(&*derived, b"exporter", &Sha256::digest(context), &mut *key);
+        Session::from_key(*key)
+    }
+}
+
+/// TLS 1.3 `HKDF-Expand-Label` over HMAC-SHA256 (RFC 8446 §7.1), filling `out`.
+pub fn hkdf_expand_label(secret: &[u8], label: &[u8], context: &[u8], out: &mut [u8]) {
+    use hmac::{Hmac, Mac};
+    type HmacSha256 = Hmac<sha2::Sha256>;
+
+    let mut info = Vec::with_capacity(4 + 6 + label.len() + context.len());
+    info.extend_from_slice(&(out.len() as u16).to_be_bytes());
+    info.push((6 + label.len()) as u8);
+    info.extend_from_slice(b"tls13 ");
+    info.extend_from_slice(label);
+    info.push(context.len() as u8);
+    info.extend_from_slice(context);
+
+    // HKDF-Expand (RFC 5869): T(i) = HMAC(secret, T(i-1) | info | i)
+    let mut prev: Vec<u8> = Vec::new();
+    for (i, chunk) in out.chunks_mut(32).enumerate() {
+        let mut mac =
+            <HmacSha256 as Mac>::new_from_slice(secret).expect("HMAC accepts any key length");
+        mac.update(&prev);
+        mac.update(&info);
+        mac.update(&[i as u8 + 1]);
+        prev = mac.finalize().into_bytes().to_vec();
+        chunk.copy_from_slice(&prev[..chunk.len()]);
+    }
+}
+
+/// Argon2 cost parameters for [`session_from_password_with`].
+#[cfg(feature = "argon2")]
+pub use argon2::Params as Argon2Params;
+
+/// Derive a session from a human password with Argon2id and the crate's default cost
+/// (19 MiB, 2 passes, 1 lane). `salt` must be at least 8 bytes.
+#[cfg(feature = "argon2")]
+pub fn session_from_password(password: &str, salt: &[u8]) -> Result<Session, Error> {
+    session_from_password_with(Argon2Params::default(), password, salt)
+}
+
+/// `session_from_password` with explicit Argon2id parameters; the output length in
+/// `params` is ignored (always 32 bytes). Bad parameters or a short salt give
+/// `Error::InvalidKey`.
+#[cfg(feature = "argon2")]
+pub fn session_from_password_with(
+    params: Argon2Params,
+    password: &str,
+    salt: &[u8],
+) -> Result<Session, Error> {
+    let argon = argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params);
+    let mut key = Zeroizing::new([0u8; 32]);
+    argon
+        .hash_password_into(password.as_bytes(), salt, &mut *key)
+        .map_err(|_| Error::InvalidKey)?;
+    Ok(Session::from_key(*key))
+}
+
+/// Derive a session with PBKDF2-HMAC-SHA256, matching OpenSSL's `PKCS5_PBKDF2_HMAC` /
+/// `openssl kdf ... PBKDF2` with a 32-byte output.
+pub fn session_from_pbkdf2(password: &[u8], salt: &[u8], iterations: u32) -> Session {
+    let mut key = Zeroizing::new([0u8; 32]);
+    pbkdf2::pbkdf2_hmac::<sha2::Sha256>(password, salt, iterations, &mut *key);
+    Session::from_key(*key)
+}
+
+/// Compare two byte strings in time that depends only on their lengths, for secret-derived
+/// values such as tags, key-ids or whole sealed frames (e.g. when de-duplicating). Inputs
+/// of different lengths compare unequal immediately; lengths are not treated as secret.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    use subtle::ConstantTimeEq;
+    a.ct_eq(b).into()
+}
+
+/// Join context fields into one AAD, each prefixed with its u32 big-endian length so that
+/// distinct field lists can never produce the same bytes.
+pub fn bind_aad(parts: &[&[u8]]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(parts.iter().map(|p| 4 + p.len()).sum());
+    for part in parts {
+        out.extend_from_slice(&(part.len() as u32).to_be_bytes());
+        out.extend_from_slice(part);
+    }
+    out
+}
+
+/// Seal `plaintext` once for several recipients, each holding a different session.
+///
+/// A random content key encrypts the payload and is wrapped (`wrap_key`) for every
+/// recipient. Frame: u16 BE slot count | per slot: u16 BE length, wrapped key | `seal`
+/// output under the content key. The slot header is bound into the payload's AAD.
+#[cfg(feature = "std")]
+pub fn seal_envelope(
+    recipients: &[Session],
+    aad: &[u8],
+    plaintext: &[u8],
+) -> Result<Vec<u8>, Error> {
+    let count = u16::try_from(recipients.len()).map_err(|_| Error::Encrypt)?;
+    let mut content_key = Zeroizing::new([0u8; 32]);
+    OsRng.fill_bytes(&mut *content_key);
+    let mut out = count.to_be_bytes().to_vec();
+    for recipient in recipients {
+        let wrapped = recipient.wrap_key(&content_key);
+        out.extend_from_slice(&(wrapped.len() as u16).to_be_bytes());
+        out.extend(wrapped);
+    }
+    let sealed = Session::from_key(*content_key).seal(&bind_aad(&[&out, aad]), plaintext)?;
+    out.extend(sealed);
+    Ok(out)
+}
+
+/// Open a `seal_envelope` frame by trying each wrapped-key slot with `session`.
+/// Fails with `Error::Decrypt` if no slot is addressed to this session.
+pub fn open_envelope(session: &Session, aad: &[u8], framed: &[u8]) -> Result<Vec<u8>, Error> {
+    fn take<'a>(buf: &mut &'a [u8], n: usize) -> Result<&'a [u8], Error> {
+        if buf.len() < n {
+            return Err(Error::Truncated);
+        }
+        let (head, rest) = buf.split_at(n);
+        *buf = rest;
+        Ok(head)
+    }
+    let mut rest = framed;
+    let count = u16::from_be_bytes(take(&mut rest, 2)?.try_into().expect("2 bytes"));
+    let mut content_key = None;
+    for _ in 0..count {
+        let len = u16::from_be_bytes(take(&mut rest, 2)?.try_into().expect("2 bytes"));
+        let wrapped = take(&mut rest, len.into())?;
+        if content_key.is_none() {
+            content_key = session.unwrap_key(wrapped).ok();
+        }
+    }
+    let header = &framed[..framed.len() - rest.len()];
+    let content_key = content_key.ok_or(Error::Decrypt)?;
+    Session::from_key(*content_key).open(&bind_aad(&[header, aad]), rest)
+}
+
+/// Derive a session from a pre-shared key with HKDF-SHA256 (RFC 5869).
+///
+/// Distinct `info` strings (e.g. per J-series message class) give independent keys from the
+/// same PSK; `salt` may be empty.
+pub fn session_from_psk_hkdf(psk: &[u8], salt: &[u8], info: &[u8]) -> Session {
+    let hk = hkdf::Hkdf::<sha2::Sha256>::new(Some(salt), psk);
+    let mut key = Zeroizing::new([0u8; 32]);
+    hk.expand(info, &mut *key)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    Session::from_key(*key)
+}
+
+/// Derive a session from a pre-shared key (placeholder for initial prototypes).
+///
+/// Deprecated in favour of [`session_from_psk_hkdf`]: a bare hash gives no salt and no
+/// domain separation. Kept because existing peers derive their keys this way.
+pub fn session_from_psk(psk: &[u8]) -> Session {
+    use blake3::hash as blake3_hash;
+    let mut key = Zeroizing::new([0u8; 32]);
+    key.copy_from_slice(blake3_hash(psk).as_bytes());
+    Session::from_key(*key)
+}
+
+/// `session_from_psk` for a hex-encoded PSK of any length, as taken on the command line.
+/// Malformed hex is `Error::InvalidKey`.
+pub fn session_from_psk_hex(hex: &str) -> Result<Session, Error> {
+    Ok(session_from_psk(&decode_hex(hex)?))
+}
+
+/// Decode hex key material into a buffer that is wiped on drop.
+fn decode_hex(hex: &str) -> Result<Zeroizing<Vec<u8>>, Error> {
+    fn nibble(c: u8) -> Result<u8, Error> {
+        match c {
+            b'0'..=b'9' => Ok(c - b'0'),
+            b'a'..=b'f' => Ok(c - b'a' + 10),
+            b'A'..=b'F' => Ok(c - b'A' + 10),
+            _ => Err(Error::InvalidKey),
+        }
+    }
+    let hex = hex.as_bytes();
+    if !hex.len().is_multiple_of(2) {
+        return Err(Error::InvalidKey);
+    }
+    let mut out = Zeroizing::new(Vec::with_capacity(hex.len() / 2));
+    for pair in hex.chunks(2) {
+        out.push((nibble(pair[0])? << 4) | nibble(pair[1])?);
+    }
+    Ok(out)
+}
+
+#[cfg(feature = "ring-backend")]
+mod ring_backend {
+    //! `ring::aead` versions of the detached primitives, used by `Session` in place of
+    //! RustCrypto for every algorithm ring implements. Same standard constructions, so
+    //! frames are byte-identical whichever backend sealed them.
+    use ring::aead::{
+        Aad, LessSafeKey, Nonce, Tag, UnboundKey, AES_128_GCM, AES_256_GCM, CHACHA20_POLY1305,
+    };
+
+    use super::{Algorithm, Error, ToString, TAG_LEN};
+
+    fn ring_algorithm(algorithm: Algorithm) -> Option<&'static ring::aead::Algorithm> {
+        match algorithm {
+            Algorithm::Aes256Gcm => Some(&AES_256_GCM),
+            Algorithm::Aes128Gcm => Some(&AES_128_GCM),
+            Algorithm::ChaCha20Poly1305 => Some(&CHACHA20_POLY1305),
+            Algorithm::XChaCha20Poly1305 => None,
+        }
+    }
+
+    /// Whether ring implements `algorithm`; the rest stay on RustCrypto.
+    pub(super) fn supports(algorithm: Algorithm) -> bool {
+        ring_algorithm(algorithm).is_some()
+    }
+
+    fn key(algorithm: Algorithm, key: &[u8]) -> Result<LessSafeKey, Error> {
+        let alg = ring_algorithm(algorithm)
+            .ok_or_else(|| Error::UnknownAlgorithm(algorithm.name().to_string()))?;
+        UnboundKey::new(alg, key)
+            .map(LessSafeKey::new)
+            .map_err(|_| Error::InvalidKey)
+    }
+
+    pub(super) fn encrypt_detached(
+        algorithm: Algorithm,
+        key_bytes: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+        buf: &mut [u8],
+    ) -> Result<[u8; TAG_LEN], Error> {
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| Error::Encrypt)?;
+        let tag = key(algorithm, key_bytes)?
+            .seal_in_place_separate_tag(nonce, Aad::from(aad), buf)
+            .map_err(|_| Error::Encrypt)?;
+        let mut out = [0u8; TAG_LEN];
+        out.copy_from_slice(tag.as_ref());
+        Ok(out)
+    }
+
+    pub(super) fn decrypt_detached(
+        algorithm: Algorithm,
+        key_bytes: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+        buf: &mut [u8],
+        tag: &[u8],
+    ) -> Result<(), Error> {
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| Error::AuthFailed)?;
+        let tag = Tag::try_from(tag).map_err(|_| Error::AuthFailed)?;
+        key(algorithm, key_bytes)?
+            .open_in_place_separate_tag(nonce, Aad::from(aad), tag, buf, 0..)
+            .map(|_| ())
+            .map_err(|_| Error::AuthFailed)
+    }
+}
+
+#[cfg(feature = "rustls")]
+pub mod tls {
+    //! Hook points to derive an application-level session key via a rustls TLS 1.3 handshake.
+    //! Integrate by exporting keying material (EKM) after handshake and feeding it to `Session::from_key`,
+    //! or let `connect` drive the client handshake and do both.
+    use std::io::{Read, Write};
+    use std::sync::Arc;
+
+    use rustls::crypto::CryptoProvider;
+    use rustls::pki_types::ServerName;
+    use rustls::{
+        ClientConfig, ClientConnection, Connection, RootCertStore, ServerConnection, StreamOwned,
+    };
+
+    use super::{Error, Session, ToString};
+
+    /// Label used by `session_from_ekm_default`.
+    pub const DEFAULT_LABEL: &[u8] = b"ads-e2ee-2026";
+
+    /// Source of exported keying material (RFC 5705 / RFC 8446 §7.5). Implemented for
+    /// rustls connections; a stub can stand in for one in tests.
+    pub trait KeyingMaterialExporter {
+        fn export_keying_material(
+            &self,
+            out: &mut [u8],
+            label: &[u8],
+            context: Option<&[u8]>,
+        ) -> Result<(), rustls::Error>;
+    }
+
+    impl KeyingMaterialExporter for Connection {
+        fn export_keying_material(
+            &self,
+            out: &mut [u8],
+            label: &[u8],
+            context: Option<&[u8]>,
+        ) -> Result<(), rustls::Error> {
+            Connection::export_keying_material(self, out, label, context).map(|_| ())
+        }
+    }
+
+    impl KeyingMaterialExporter for ClientConnection {
+        fn export_keying_material(
+            &self,
+            out: &mut [u8],
+            label: &[u8],
+            context: Option<&[u8]>,
+        ) -> Result<(), rustls::Error> {
+            (**self)
+                .export_keying_material(out, label, context)
+                .map(|_| ())
+        }
+    }
+
+    impl KeyingMaterialExporter for ServerConnection {
+        fn export_keying_material(
+            &self,
+            out: &mut [u8],
+            label: &[u8],
+            context: Option<&[u8]>,
+        ) -> Result<(), rustls::Error> {
+            (**self)
+                .export_keying_material(out, label, context)
+                .map(|_| ())
+        }
+    }
+
+    /// Export 32 bytes of keying material under `label`/`context` and key a session with them.
+    /// Distinct labels (e.g. one per protocol revision) give independent sessions from the
+    /// same connection. `None` if the handshake hasn't completed.
+    pub fn session_from_ekm(
+        conn: &impl KeyingMaterialExporter,
+        label: &[u8],
+        context: Option<&[u8]>,
+    ) -> Option<Session> {
+        let mut out = zeroize::Zeroizing::new([0u8; 32]);
+        conn.export_keying_material(&mut *out, label, context)
+            .ok()?;
+        Some(Session::from_key(*out))
+    }
+
+    /// `session_from_ekm` with `DEFAULT_LABEL` and an empty context, as earlier releases did.
+    pub fn session_from_ekm_default(conn: &impl KeyingMaterialExporter) -> Option<Session> {
+        session_from_ekm(conn, DEFAULT_LABEL, Some(&[]))
+    }
+
+    /// Run a TLS 1.3 client handshake with `server_name` over `stream` (e.g. a connected
+    /// `TcpStream`), verifying the server against `roots`, and derive the default EKM
+    /// session (`session_from_ekm_default`). Returns the TLS stream, ready for application
+    /// data, alongside the session. Any handshake or I/O failure is `Error::Handshake`.
+    pub fn connect<S: Read + Write>(
+        server_name: &str,
+        mut stream: S,
+        roots: RootCertStore,
+    ) -> Result<(StreamOwned<ClientConnection, S>, Session), Error> {
+        // Use the process-wide provider if the application installed one; don't rely on
+        // rustls picking one, which fails when several are compiled in.
+        let provider = CryptoProvider::get_default()
+            .cloned()
+            .unwrap_or_else(|| Arc::new(rustls::crypto::aws_lc_rs::default_provider()));
+        let config = ClientConfig::builder_with_provider(provider)
+            .with_protocol_versions(&[&rustls::version::TLS13])
+            .map_err(|_| Error::Handshake)?
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        let name = ServerName::try_from(server_name.to_string()).map_err(|_| Error::Handshake)?;
+        let mut conn =
+            ClientConnection::new(Arc::new(config), name).map_err(|_| Error::Handshake)?;
+        while conn.is_handshaking() {
+            conn.complete_io(&mut stream)
+                .map_err(|_| Error::Handshake)?;
+        }
+        while conn.wants_write() {
+            conn.write_tls(&mut stream).map_err(|_| Error::Handshake)?;
+        }
+        let session = session_from_ekm_default(&conn).ok_or(Error::Handshake)?;
+        Ok((StreamOwned::new(conn, stream), session))
+    }
+}
+
+#[cfg(feature = "x25519")]
+pub mod handshake {
+    //! Minimal Noise_NN-style handshake: each side sends one ephemeral X25519 public key,
+    //! then both run HKDF-SHA256 over the DH output, salted with the transcript.
+    //! Gives forward secrecy but no authentication; pair with a signature or PSK if needed.
+    use aes_gcm::aead::OsRng;
+    use hkdf::Hkdf;
+    use sha2::Sha256;
+    use x25519_dalek::{EphemeralSecret, PublicKey};
+
+    use super::{Error, Session};
+
+    const PROTOCOL: &[u8] = b"ads-e2ee Noise_NN_25519_SHA256";
+
+    /// Initiator state held between sending its key and receiving the responder's.
+    pub struct Initiator {
+        secret: EphemeralSecret,
+        public: PublicKey,
+    }
+
+    /// Start a handshake; send the returned public key to the responder.
+    pub fn handshake_initiator() -> (Initiator, [u8; 32]) {
+        let secret = EphemeralSecret::random_from_rng(OsRng);
+        let public = PublicKey::from(&secret);
+        (Initiator { secret, public }, public.to_bytes())
+    }
+
+    impl Initiator {
+        /// Complete the handshake with the responder's public key.
+        pub fn finish(self, responder_pub: &[u8; 32]) -> Result<Session, Error> {
+            let peer = PublicKey::from(*responder_pub);
+            let shared = self.secret.diffie_hellman(&peer);
+            derive(shared, &self.public.to_bytes(), responder_pub)
+        }
+    }
+
+    /// Role-free variant for peers that exchange keys simultaneously (e.g. two bridges):
+    /// each side calls `generate`, sends `public_key`, and calls `into_session` with the
+    /// peer's. The transcript orders the two keys canonically, so both derive one session.
+    pub struct KeyExchange {
+        secret: EphemeralSecret,
+        public: PublicKey,
+    }
+
+    impl KeyExchange {
+        pub fn generate() -> Self {
+            let secret = EphemeralSecret::random_from_rng(OsRng);
+            let public = PublicKey::from(&secret);
+            Self { secret, public }
+        }
+
+        /// Public key to send to the peer.
+        pub fn public_key(&self) -> [u8; 32] {
+            self.public.to_bytes()
+        }
+
+        /// Run X25519 with the peer's public key and derive the shared session.
+        pub fn into_session(self, peer_pub: &[u8; 32]) -> Result<Session, Error> {
+            let ours = self.public.to_bytes();
+            let shared = self.secret.diffie_hellman(&PublicKey::from(*peer_pub));
+            let (lo, hi) = if ours <= *peer_pub {
+                (&ours, peer_pub)
+            } else {
+                (peer_pub, &ours)
+            };
+            derive(shared, lo, hi)
+        }
+    }
+
+    /// Answer an initiator; send the returned public key back and keep the session.
+    pub fn handshake_responder(initiator_pub: &[u8; 32]) -> Result<([u8; 32], Session), Error> {
+        let secret = EphemeralSecret::random_from_rng(OsRng);
+        let public = PublicKey::from(&secret).to_bytes();
+        let shared = secret.diffie_hellman(&PublicKey::from(*initiator_pub));
+        Ok((public, derive(shared, initiator_pub, &public)?))
+    }
+
+    fn derive(
+        shared: x25519_dalek::SharedSecret,
+        initiator_pub: &[u8; 32],
+        responder_pub: &[u8; 32],
+    ) -> Result<Session, Error> {
+        // Reject low-order peer keys that force an all-zero shared secret.
+        if !shared.was_contributory() {
+            return Err(Error::Handshake);
+        }
+        let mut transcript = [0u8; 64];
+        transcript[..32].copy_from_slice(initiator_pub);
+        transcript[32..].copy_from_slice(responder_pub);
+        let hk = Hkdf::<Sha256>::new(Some(&transcript), shared.as_bytes());
+        let mut key = zeroize::Zeroizing::new([0u8; 32]);
+        hk.expand(PROTOCOL, &mut *key)
+            .map_err(|_| Error::Handshake)?;
+        Ok(Session::from_key(*key))
+    }
+}
+
+#[cfg(feature = "ed25519")]
+pub mod signing {
+    //! Encrypt-then-sign: an Ed25519 signature over the sealed frame is appended to it, binding
+    //! authorship for non-repudiation on top of the AEAD's integrity.
+    use aes_gcm::aead::OsRng;
+    use ed25519_dalek::{Signature, Signer, Verifier, SIGNATURE_LENGTH};
+    pub use ed25519_dalek::{SigningKey, VerifyingKey};
+
+    use super::{Error, Session};
+
+    /// A `Session` plus the sender's Ed25519 signing key.
+    pub struct SigningSession {
+        session: Session,
+        signing_key: SigningKey,
+    }
+
+    impl SigningSession {
+        pub fn new(session: Session, signing_key: SigningKey) -> Self {
+            Self {
+                session,
+                signing_key,
+            }
+        }
+
+        /// Pair `session` with a freshly generated signing key.
+        pub fn generate(session: Session) -> Self {
+            Self::new(session, SigningKey::generate(&mut OsRng))
+        }
+
+        /// Public key peers need to pass to `open_signed`.
+        pub fn verifying_key(&self) -> VerifyingKey {
+            self.signing_key.verifying_key()
+        }
+
+        /// Seal, then append a 64-byte signature over the sealed frame.
+        pub fn seal_signed(&self, aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+            let mut framed = self.session.seal(aad, plaintext)?;
+            let sig = self.signing_key.sign(&framed);
+            framed.extend_from_slice(&sig.to_bytes());
+            Ok(framed)
+        }
+
+        /// Verify the trailing signature against `sender`, then open the frame.
+        pub fn open_signed(
+            &self,
+            sender: &VerifyingKey,
+            aad: &[u8],
+            framed: &[u8],
+        ) -> Result<Vec<u8>, Error> {
+            let split = framed
+                .len()
+                .checked_sub(SIGNATURE_LENGTH)
+                .ok_or(Error::BadSignature)?;
+            let (frame, sig) = framed.split_at(split);
+            let sig = Signature::from_slice(sig).map_err(|_| Error::BadSignature)?;
+            sender
+                .verify(frame, &sig)
+                .map_err(|_| Error::BadSignature)?;
+            self.session.open(aad, frame)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_hex_accepts_32_byte_keys_only() {
+        let hex = "00112233445566778899aabbccddeeffFFEEDDCCBBAA99887766554433221100";
+        let s = Session::from_hex(hex).unwrap();
+        assert_eq!(hex::encode(*s.key), hex.to_ascii_lowercase());
+        // Odd length, non-hex, and a valid but short key.
+        assert!(matches!(
+            Session::from_hex(&hex[1..]),
+            Err(Error::InvalidKey)
+        ));
+        assert!(matches!(
+            Session::from_hex(&hex.replace('a', "g")),
+            Err(Error::InvalidKey)
+        ));
+        assert!(matches!(Session::from_hex("0011"), Err(Error::InvalidKey)));
+    }
+
+    #[test]
+    fn psk_hex_matches_raw_psk() {
+        let s = session_from_psk_hex("736563726574").unwrap();
+        assert_eq!(*s.key, *session_from_psk(b"secret").key);
+        assert!(matches!(
+            session_from_psk_hex("7365z"),
+            Err(Error::InvalidKey)
+        ));
+    }
+
+    #[test]
+    fn constant_time_eq_matches_slice_eq() {
+        let cases: [(&[u8], &[u8]); 6] = [
+            (b"", b""),
+            (b"a", b"a"),
+            (b"a", b"b"),
+            (b"abc", b"ab"),
+            (&[0u8; 64], &[0u8; 64]),
+            (&[0u8; 64], &[0u8; 63]),
+        ];
+        for (a, b) in cases {
+            assert_eq!(constant_time_eq(a, b), a == b, "{a:?} vs {b:?}");
+        }
+        let mut frame = Session::from_key([1u8; 32]).seal(b"", b"dup").unwrap();
+        let copy = frame.clone();
+        assert!(constant_time_eq(&frame, &copy));
+        *frame.last_mut().unwrap() ^= 0x80;
+        assert!(!constant_time_eq(&frame, &copy));
+    }
+
+    #[test]
+    fn psk_hkdf_separates_contexts() {
+        let a = session_from_psk_hkdf(b"shared psk", b"ads-salt", b"j3.2");
+        // Reference value from Python `cryptography`'s HKDF(SHA256, 32, salt, info).
+        assert_eq!(
+            hex::encode(*a.key),
+            "b3e4a0c98e95b26d2d9dd108c9507e554fa272cb6c3afa7a3da471fe837921e0"
+        );
+        let again = session_from_psk_hkdf(b"shared psk", b"ads-salt", b"j3.2");
+        assert_eq!(*a.key, *again.key);
+        let other = session_from_psk_hkdf(b"shared psk", b"ads-salt", b"j7.0");
+        assert_ne!(*a.key, *other.key);
+        assert!(other.open(b"", &a.seal(b"", b"x").unwrap()).is_err());
+    }
+
+    #[test]
+    fn frame_chain_detects_dropped_frame() {
+        let sess = session_from_psk(b"chain");
+        let mut tx = sess.frame_chain();
+        let frames: Vec<_> = (0u8..4).map(|i| sess.seal(b"c", &[i]).unwrap()).collect();
+        let tags: Vec<_> = frames.iter().map(|f| tx.link(f)).collect();
+
+        let mut in_order = sess.frame_chain();
+        for (frame, tag) in frames.iter().zip(&tags) {
+            in_order.verify(frame, tag).unwrap();
+        }
+
+        let mut rx = sess.frame_chain();
+        rx.verify(&frames[0], &tags[0]).unwrap();
+        // frame 1 is lost in transit
+        assert!(matches!(
+            rx.verify(&frames[2], &tags[2]),
+            Err(Error::ChainBroken)
+        ));
+        assert!(matches!(
+            rx.verify(&frames[3], &tags[3]),
+            Err(Error::ChainBroken)
+        ));
+    }
+
+    #[test]
+    fn key_ring_selects_session_by_epoch() {
+        let mut sender = KeyRing::new();
+        sender.insert(3, session_from_psk(b"day 3"));
+        let framed = sender.seal_epoch(3, b"k", b"track").unwrap();
+        assert_eq!(framed[0], 3);
+
+        let mut ring = KeyRing::new();
+        for epoch in 1..=5u8 {
+            ring.insert(epoch, session_from_psk(format!("day {epoch}").as_bytes()));
+        }
+        assert_eq!(ring.open_any(b"k", &framed).unwrap(), b"track");
+
+        let mut unknown = framed.clone();
+        unknown[0] = 9;
+        assert!(matches!(
+            ring.open_any(b"k", &unknown),
+            Err(Error::UnknownEpoch(9))
+        ));
+        assert!(matches!(
+            ring.seal_epoch(9, b"k", b"track"),
+            Err(Error::UnknownEpoch(9))
+        ));
+        assert!(matches!(ring.open_any(b"k", &[]), Err(Error::Truncated)));
+    }
+
+    #[test]
+    fn sessions_zeroize_on_drop() {
+        fn assert_zeroize_on_drop<T: ZeroizeOnDrop + Clone>() {}
+        assert_zeroize_on_drop::<Session>();
+    }
+
+    #[test]
+    fn counter_nonces_increase_and_exhaust() {
+        let tx = CounterSession::new(session_from_psk(b"ctr"));
+        let a = tx.seal(b"c", b"one").unwrap();
+        let b = tx.seal(b"c", b"two").unwrap();
+        assert_eq!(a[..4], b[..4], "prefix is fixed per session");
+        assert!(a[4..12] < b[4..12]);
+        assert_eq!(u64::from_be_bytes(b[4..12].try_into().unwrap()), 1);
+        assert_eq!(session_from_psk(b"ctr").open(b"c", &b).unwrap(), b"two");
+
+        tx.counter
+            .store(u64::MAX - 1, std::sync::atomic::Ordering::Relaxed);
+        assert!(tx.seal(b"c", b"last").is_ok());
+        assert!(matches!(tx.seal(b"c", b"wrap"), Err(Error::NonceExhausted)));
+    }
+
+    #[test]
+    fn replay_window_in_order_and_reordered() {
+        let mut w = ReplayWindow::new();
+        for seq in 1..=5 {
+            assert!(w.check_and_update(seq));
+        }
+        assert!(w.check_and_update(10));
+        assert!(w.check_and_update(7), "reordered within window");
+        assert!(w.check_and_update(6));
+    }
+
+    #[test]
+    fn replay_window_rejects_duplicates_and_far_past() {
+        let mut w = ReplayWindow::new();
+        assert!(w.check_and_update(100));
+        assert!(!w.check_and_update(100));
+        assert!(w.check_and_update(99));
+        assert!(!w.check_and_update(99));
+        assert!(w.check_and_update(200));
+        assert!(
+            !w.check_and_update(200 - ReplayWindow::SIZE),
+            "outside window"
+        );
+        assert!(w.check_and_update(200 - ReplayWindow::SIZE + 1));
+    }
+
+    #[test]
+    fn seq_frames_reject_replay_and_forged_seq() {
+        let s = session_from_psk(b"seq");
+        let mut w = ReplayWindow::new();
+        let f1 = s.seal_seq(1, b"udp", b"a").unwrap();
+        let f2 = s.seal_seq(2, b"udp", b"b").unwrap();
+        assert_eq!(s.open_seq(&mut w, b"udp", &f2).unwrap(), (2, b"b".to_vec()));
+        assert_eq!(s.open_seq(&mut w, b"udp", &f1).unwrap(), (1, b"a".to_vec()));
+        assert!(matches!(
+            s.open_seq(&mut w, b"udp", &f2),
+            Err(Error::Replay(2))
+        ));
+
+        let mut forged = s.seal_seq(3, b"udp", b"c").unwrap();
+        forged[7] = 4;
+        assert!(matches!(
+            s.open_seq(&mut w, b"udp", &forged),
+            Err(Error::AuthFailed)
+        ));
+        // The forgery didn't consume seq 4.
+        let f4 = s.seal_seq(4, b"udp", b"d").unwrap();
+        assert!(s.open_seq(&mut w, b"udp", &f4).is_ok());
+    }
+
+    #[test]
+    fn header_frame_roundtrips_within_max_age() {
+        let s = session_from_psk(b"hdr");
+        let framed = s.seal_with_header(1_000, b"track 42").unwrap();
+        assert_eq!(&framed[..12], &[0, 0, 0, 0, 0, 0, 0x03, 0xE8, 0, 0, 0, 8]);
+        assert_eq!(
+            s.open_with_header(&framed, 1_005, Some(10)).unwrap(),
+            (1_000, b"track 42".to_vec())
+        );
+        assert!(s.open_with_header(&framed, u64::MAX, None).is_ok());
+    }
+
+    #[test]
+    fn header_frame_rejects_tampered_length() {
+        let s = session_from_psk(b"hdr");
+        let mut framed = s.seal_with_header(1_000, b"track 42").unwrap();
+        framed[11] = 4;
+        assert!(matches!(
+            s.open_with_header(&framed, 1_000, None),
+            Err(Error::AuthFailed)
+        ));
+        assert!(matches!(
+            s.open_with_header(&framed[..11], 1_000, None),
+            Err(Error::Truncated)
+        ));
+    }
+
+    #[test]
+    fn header_frame_rejects_expired_timestamp() {
+        let s = session_from_psk(b"hdr");
+        let framed = s.seal_with_header(1_000, b"track 42").unwrap();
+        assert!(matches!(
+            s.open_with_header(&framed, 1_011, Some(10)),
+            Err(Error::Expired(1_000))
+        ));
+    }
+
+    #[test]
+    fn ratchet_advances_in_lockstep() {
+        let mut tx = Ratchet::new(session_from_psk(b"ratchet"), 4);
+        let mut rx = Ratchet::new(session_from_psk(b"ratchet"), 4);
+        let first_key = *tx.session.key;
+        for i in 0..3u8 {
+            let framed = tx.seal(b"udp", &[i]).unwrap();
+            assert_eq!(rx.open(b"udp", &framed).unwrap(), [i]);
+        }
+        assert_eq!((tx.index(), rx.index()), (3, 3));
+        assert_eq!(*tx.session.key, *rx.session.key);
+        assert_ne!(*tx.session.key, first_key);
+    }
+
+    #[test]
+    fn ratchet_recovers_after_dropped_message() {
+        let mut tx = Ratchet::new(session_from_psk(b"ratchet"), 1);
+        let mut rx = Ratchet::new(session_from_psk(b"ratchet"), 1);
+        let f0 = tx.seal(b"", b"m0").unwrap();
+        let _dropped = tx.seal(b"", b"m1").unwrap();
+        let f2 = tx.seal(b"", b"m2").unwrap();
+        assert_eq!(rx.open(b"", &f0).unwrap(), b"m0");
+        assert_eq!(rx.open(b"", &f2).unwrap(), b"m2");
+        // Replaying an already-consumed index fails: its key is gone.
+        assert!(matches!(rx.open(b"", &f2), Err(Error::Replay(2))));
+
+        // A gap wider than `max_skip` is refused without moving the ratchet.
+        let _ = (tx.seal(b"", b"m3"), tx.seal(b"", b"m4"));
+        let f5 = tx.seal(b"", b"m5").unwrap();
+        assert!(matches!(rx.open(b"", &f5), Err(Error::RatchetSkip(5))));
+        assert_eq!(rx.index(), 3);
+    }
+
+    #[test]
+    fn envelope_opens_for_each_recipient_only() {
+        let recipients: Vec<_> = [b"r1", b"r2", b"r3"]
+            .iter()
+            .map(|psk| session_from_psk(*psk))
+            .collect();
+        let framed = seal_envelope(&recipients, b"env", b"broadcast").unwrap();
+        for r in &recipients {
+            assert_eq!(open_envelope(r, b"env", &framed).unwrap(), b"broadcast");
+        }
+        assert!(matches!(
+            open_envelope(&session_from_psk(b"outsider"), b"env", &framed),
+            Err(Error::Decrypt)
+        ));
+        assert!(matches!(
+            open_envelope(&recipients[0], b"other", &framed),
+            Err(Error::AuthFailed)
+        ));
+        assert!(matches!(
+            open_envelope(&recipients[0], b"env", &framed[..40]),
+            Err(Error::Truncated)
+        ));
+    }
+
+    #[test]
+    fn wrap_key_roundtrip() {
+        let master = session_from_psk(b"master");
+        let key = [7u8; 32];
+        let wrapped = master.wrap_key(&key);
+        assert_eq!(*master.unwrap_key(&wrapped).unwrap(), key);
+        assert!(session_from_psk(b"other").unwrap_key(&wrapped).is_err());
+    }
+
+    #[test]
+    fn wrap_key_detects_tampering_and_bad_length() {
+        let master = session_from_psk(b"master");
+        let mut wrapped = master.wrap_key(&[7u8; 32]);
+        wrapped[20] ^= 0x01;
+        assert!(matches!(
+            master.unwrap_key(&wrapped),
+            Err(Error::AuthFailed)
+        ));
+
+        let short = master.seal(KEY_WRAP_AAD, &[7u8; 16]).unwrap();
+        assert!(matches!(master.unwrap_key(&short), Err(Error::InvalidKey)));
+    }
+
+    #[test]
+    fn from_config_known_algorithms() {
+        let key = [3u8; 32];
+        for (name, alg) in [
+            ("aes256gcm", Algorithm::Aes256Gcm),
+            ("chacha20poly1305", Algorithm::ChaCha20Poly1305),
+            ("ChaCha20Poly1305", Algorithm::ChaCha20Poly1305),
+            ("xchacha20poly1305", Algorithm::XChaCha20Poly1305),
+        ] {
+            let s = Session::from_config(&key, name).unwrap();
+            assert_eq!(s.algorithm(), alg);
+            let ct = s.seal(b"cfg", b"payload").unwrap();
+            assert_eq!(s.open(b"cfg", &ct).unwrap(), b"payload");
+        }
+        assert!(matches!(
+            Session::from_config(&key[..16], "aes256gcm"),
+            Err(Error::InvalidKey)
+        ));
+    }
+
+    #[test]
+    fn roundtrip_per_algorithm() {
+        for alg in [
+            Algorithm::Aes256Gcm,
+            Algorithm::ChaCha20Poly1305,
+            Algorithm::XChaCha20Poly1305,
+        ] {
+            let s = Session::from_key_with(alg, [9u8; 32]);
+            let framed = s.seal(b"a", b"telemetry").unwrap();
+            assert_eq!(framed.len(), alg.nonce_len() + 9 + 16);
+            assert_eq!(s.open(b"a", &framed).unwrap(), b"telemetry");
+        }
+        assert_eq!(
+            Session::from_key([9u8; 32]).algorithm(),
+            Algorithm::Aes256Gcm
+        );
+    }
+
+    #[test]
+    fn xchacha_nonces_do_not_collide() {
+        let s = Session::from_key_with(Algorithm::XChaCha20Poly1305, [5u8; 32]);
+        let mut seen = std::collections::HashSet::new();
+        for _ in 0..100_000 {
+            let framed = s.seal(b"", b"").unwrap();
+            assert!(seen.insert(framed[..24].to_vec()), "nonce collision");
+        }
+        assert!(matches!(s.open(b"", &[0u8; 23]), Err(Error::Truncated)));
+    }
+
+    #[test]
+    fn aes128_roundtrip_and_mismatch_is_an_error() {
+        let aes128 = Session::from_key_128([6u8; 16]);
+        assert_eq!(aes128.algorithm(), Algorithm::Aes128Gcm);
+        let framed = aes128.seal(b"r", b"radio").unwrap();
+        assert_eq!(framed.len(), 12 + 5 + 16);
+        assert_eq!(aes128.open(b"r", &framed).unwrap(), b"radio");
+        let via_config = Session::from_config(&[6u8; 16], "aes128gcm").unwrap();
+        assert_eq!(via_config.open(b"r", &framed).unwrap(), b"radio");
+
+        let aes256 = Session::from_key([6u8; 32]);
+        assert!(matches!(
+            aes128.open(b"r", &aes256.seal(b"r", b"radio").unwrap()),
+            Err(Error::AuthFailed)
+        ));
+        assert!(matches!(aes256.open(b"r", &framed), Err(Error::AuthFailed)));
+    }
+
+    #[test]
+    fn in_place_matches_allocating_api() {
+        for alg in [
+            Algorithm::Aes256Gcm,
+            Algorithm::Aes128Gcm,
+            Algorithm::ChaCha20Poly1305,
+            Algorithm::XChaCha20Poly1305,
+        ] {
+            let s = Session::from_key_with(alg, [4u8; 32]);
+            let mut buf = b"telemetry".to_vec();
+            s.seal_in_place(b"ip", &mut buf).unwrap();
+            assert_eq!(buf.len(), alg.nonce_len() + 9 + 16);
+            assert_eq!(s.open(b"ip", &buf).unwrap(), b"telemetry");
+
+            let mut frame = s.seal(b"ip", b"telemetry").unwrap();
+            s.open_in_place(b"ip", &mut frame).unwrap();
+            assert_eq!(frame, b"telemetry");
+
+            let mut bad = s.seal(b"ip", b"telemetry").unwrap();
+            assert!(matches!(
+                s.open_in_place(b"other", &mut bad),
+                Err(Error::AuthFailed)
+            ));
+            assert!(s.open_in_place(b"ip", &mut vec![0u8; 3]).is_err());
+        }
+    }
+
+    #[test]
+    fn open_reports_failure_cause() {
+        let s = session_from_psk(b"causes");
+        let framed = s.seal(b"a", b"payload").unwrap();
+        assert!(matches!(s.open(b"a", &framed[..11]), Err(Error::Truncated)));
+        assert!(matches!(
+            s.open(b"a", &framed[..12 + 15]),
+            Err(Error::Truncated)
+        ));
+        let mut bad_tag = framed.clone();
+        *bad_tag.last_mut().unwrap() ^= 0x80;
+        assert!(matches!(s.open(b"a", &bad_tag), Err(Error::AuthFailed)));
+        assert!(matches!(s.open(b"b", &framed), Err(Error::AuthFailed)));
+    }
+
+    /// Reference ciphertext from Python `cryptography`:
+    /// `AESGCM(b"\x11" * 32).encrypt(bytes(range(12)), b"j3.2 kat", b"kat")`.
+    #[test]
+    fn seal_with_nonce_matches_known_answer() {
+        let nonce: [u8; 12] = core::array::from_fn(|i| i as u8);
+        let s = Session::from_key([0x11; 32]);
+        let framed = s.seal_with_nonce(&nonce, b"kat", b"j3.2 kat").unwrap();
+        assert_eq!(framed[..12], nonce);
+        assert_eq!(
+            hex::encode(&framed[12..]),
+            "79d098215bc854806a3f289205ffffa462ac50f1ac82ef81"
+        );
+        assert_eq!(s.open(b"kat", &framed).unwrap(), b"j3.2 kat");
+        let x = Session::from_key_with(Algorithm::XChaCha20Poly1305, [0x11; 32]);
+        assert!(matches!(
+            x.seal_with_nonce(&nonce, b"kat", b""),
+            Err(Error::Encrypt)
+        ));
+    }
+
+    #[test]
+    fn wrong_algorithm_fails_to_open() {
+        let aes = Session::from_key_with(Algorithm::Aes256Gcm, [9u8; 32]);
+        let chacha = Session::from_key_with(Algorithm::ChaCha20Poly1305, [9u8; 32]);
+        assert!(matches!(
+            chacha.open(b"a", &aes.seal(b"a", b"x").unwrap()),
+            Err(Error::AuthFailed)
+        ));
+        assert!(matches!(
+            aes.open(b"a", &chacha.seal(b"a", b"x").unwrap()),
+            Err(Error::AuthFailed)
+        ));
+    }
+
+    #[test]
+    fn from_config_unknown_algorithm() {
+        assert!(matches!(
+            Session::from_config(&[0u8; 32], "rot13"),
+            Err(Error::UnknownAlgorithm(name)) if name == "rot13"
+        ));
+    }
+
+    /// Reference values from an OpenSSL 3.0 TLS 1.3 handshake (TLS_AES_128_GCM_SHA256):
+    /// `EXPORTER_SECRET` from `-keylogfile` and the output of
+    /// `s_client -keymatexport "EXPERIMENTAL ads e2ee" -keymatexportlen 32`.
+    #[test]
+    fn from_tls_exporter_matches_openssl() {
+        let secret = [
+            0x24, 0x4a, 0xda, 0xa0, 0xf6, 0x83, 0x7c, 0xb4, 0x88, 0xc9, 0x9c, 0xba, 0x38, 0xcc,
+            0x48, 0x7c, 0x5b, 0xc6, 0xe2, 0x41, 0x97, 0x4a, 0x6c, 0xa1, 0x70, 0xd4, 0x31, 0x69,
+            0xcc, 0x76, 0xc6, 0x8f,
+        ];
+        let expected = [
+            0xec, 0xac, 0xf2, 0xbb, 0x26, 0xf0, 0xc4, 0xe1, 0x7d, 0xc8, 0x37, 0xc3, 0xe5, 0x5b,
+            0xeb, 0x88, 0xa3, 0x24, 0x70, 0x30, 0x12, 0x7a, 0xde, 0xb0, 0xc8, 0x91, 0xda, 0x4e,
+            0xda, 0xff, 0x9f, 0x7a,
+        ];
+        let derived = Session::from_tls_exporter(&secret, "EXPERIMENTAL ads e2ee", b"");
+        assert_eq!(*derived.key, expected);
+
+        let ct = Session::from_key(expected).seal(b"tls", b"x").unwrap();
+        assert_eq!(derived.open(b"tls", &ct).unwrap(), b"x");
+        assert_ne!(
+            Session::from_tls_exporter(&secret, "EXPERIMENTAL ads e2ee", b"ctx").key[..],
+            expected
+        );
+    }
+
+    #[test]
+    fn public_beacon_roundtrip() {
+        let s = session_from_psk(b"beacon");
+        let framed = s.seal_public(b"node-7 online");
+        // Data travels in the clear.
+        assert_eq!(&framed[12..framed.len() - 16], b"node-7 online");
+        assert_eq!(s.open_public(&framed).unwrap(), b"node-7 online");
+        assert!(session_from_psk(b"other").open_public(&framed).is_err());
+    }
+
+    #[test]
+    fn public_beacon_tamper_rejected() {
+        let s = session_from_psk(b"beacon");
+        let mut framed = s.seal_public(b"node-7 online");
+        framed[12] ^= 0x20;
+        assert!(matches!(s.open_public(&framed), Err(Error::AuthFailed)));
+        assert!(matches!(
+            s.open_public(&framed[..20]),
+            Err(Error::Truncated)
+        ));
+    }
+
+    /// Generated with `openssl kdf -keylen 32 -kdfopt digest:SHA256
+    /// -kdfopt pass:correct-horse-battery -kdfopt salt:ads-bridge-salt -kdfopt iter:4096 PBKDF2`.
+    const OPENSSL_PBKDF2_HEX: &str =
+        "22c346ab26405f3c71356eede69305acc72f37b6c09f4134a8c0dd1f0f11d015";
+
+    #[test]
+    fn pbkdf2_matches_openssl() {
+        let s = session_from_pbkdf2(b"correct-horse-battery", b"ads-bridge-salt", 4096);
+        assert_eq!(hex::encode(*s.key), OPENSSL_PBKDF2_HEX);
+    }
+
+    #[test]
+    fn fingerprint_matches_iff_keys_match() {
+        let a = Session::from_key([1u8; 32]);
+        let fp = a.fingerprint();
+        assert_eq!(fp.len(), 8);
+        assert!(fp
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || ('2'..='7').contains(&c)));
+        assert_eq!(fp, Session::from_key([1u8; 32]).fingerprint());
+        assert_ne!(fp, Session::from_key([2u8; 32]).fingerprint());
+
+        // Derived from a subkey, not a base32 prefix of the raw key bytes.
+        let mut raw = [0u8; 32];
+        raw[0] = 0xFF;
+        assert_ne!(Session::from_key(raw).fingerprint(), "74AAAAAA");
+        assert_ne!(Session::from_key([0u8; 32]).fingerprint(), "AAAAAAAA");
+    }
+
+    #[test]
+    fn key_id_is_stable_per_key() {
+        let a = Session::from_key([1u8; 32]);
+        assert_eq!(a.key_id(), Session::from_key([1u8; 32]).key_id());
+        assert_eq!(a.key_id(), a.clone().key_id());
+        assert_ne!(a.key_id(), Session::from_key([2u8; 32]).key_id());
+        assert_ne!(a.key_id()[..], [1u8; 8]);
+    }
+
+    #[test]
+    fn open_with_meta_reports_what_seal_wrote() {
+        let sess = Session::from_config(&[7u8; 32], "chacha20poly1305").unwrap();
+        let framed = sess.seal(b"m", b"payload").unwrap();
+        let (meta, pt) = sess.open_with_meta(b"m", &framed).unwrap();
+        assert_eq!(pt, b"payload");
+        assert_eq!(meta.algorithm, Algorithm::ChaCha20Poly1305);
+        assert_eq!(meta.key_id, sess.key_id());
+        assert_eq!(meta.nonce[..], framed[..12]);
+        assert_ne!(meta.key_id, Session::from_key([8u8; 32]).key_id());
+        assert!(matches!(
+            sess.open_with_meta(b"other", &framed),
+            Err(Error::AuthFailed)
+        ));
+    }
+
+    #[test]
+    fn sealed_sender_hides_key_id() {
+        let alice = Session::from_key([3u8; 32]);
+        let bob = Session::from_key([4u8; 32]);
+        let id = alice.key_id();
+        let a = alice.seal_sealed_sender(b"s", b"secret").unwrap();
+        let b = alice.seal_sealed_sender(b"s", b"secret").unwrap();
+        assert!(!a.windows(id.len()).any(|w| w == id));
+        assert_ne!(a[12..20], b[12..20], "masked key-id must differ per frame");
+
+        let candidates = [bob.clone(), alice.clone()];
+        let recipient = candidates
+            .iter()
+            .find(|s| s.is_sealed_sender_for(&a))
+            .unwrap();
+        assert_eq!(recipient.open_sealed_sender(b"s", &a).unwrap(), b"secret");
+        assert!(matches!(
+            bob.open_sealed_sender(b"s", &a),
+            Err(Error::Decrypt)
+        ));
+
+        let mut tampered = a.clone();
+        tampered[12] ^= 1;
+        assert!(alice.open_sealed_sender(b"s", &tampered).is_err());
+    }
+
+    #[test]
+    fn bind_aad_is_unambiguous() {
+        assert_ne!(bind_aad(&[b"ab", b"c"]), bind_aad(&[b"a", b"bc"]));
+        assert_ne!(bind_aad(&[b"ab"]), bind_aad(&[b"ab", b""]));
+    }
+
+    #[cfg(feature = "base64")]
+    #[test]
+    fn base64_roundtrip() {
+        let s = session_from_psk(b"psk");
+        let text = s.seal_base64(b"aad", b"hello").unwrap();
+        assert!(text.is_ascii());
+        assert_eq!(s.open_base64(b"aad", &text).unwrap(), b"hello");
+        assert!(matches!(
+            s.open_base64(b"other", &text),
+            Err(Error::AuthFailed)
+        ));
+    }
+
+    #[cfg(feature = "base64")]
+    #[test]
+    fn base64_rejects_invalid_input() {
+        let s = session_from_psk(b"psk");
+        assert!(matches!(
+            s.open_base64(b"aad", "not*base64!"),
+            Err(Error::Base64)
+        ));
+    }
+
+    #[cfg(feature = "argon2")]
+    #[test]
+    fn password_sessions_depend_on_salt() {
+        // Low cost so the test stays fast in debug builds.
+        let params = || Argon2Params::new(1024, 1, 1, None).unwrap();
+        let a = session_from_password_with(params(), "hunter2", b"salt-one").unwrap();
+        let b = session_from_password_with(params(), "hunter2", b"salt-one").unwrap();
+        assert_eq!(*a.key, *b.key);
+        let c = session_from_password_with(params(), "hunter2", b"salt-two").unwrap();
+        assert_ne!(*a.key, *c.key);
+        assert!(matches!(
+            session_from_password("hunter2", b"short"),
+            Err(Error::InvalidKey)
+        ));
+    }
+
+    #[cfg(feature = "compress")]
+    #[test]
+    fn compressed_roundtrip_shrinks_telemetry() {
+        let s = session_from_psk(b"zstd");
+        let json = r#"{"track":42,"lat":45.0,"lon":-122.0,"alt_m":1500.0}"#.repeat(20);
+        let framed = s.seal_compressed(b"z", json.as_bytes(), 3).unwrap();
+        assert!(framed.len() < json.len());
+        assert_eq!(
+            s.open_compressed(b"z", &framed, 4096).unwrap(),
+            json.as_bytes()
+        );
+
+        // Incompressible input is stored raw and still opens.
+        let framed = s.seal_compressed(b"z", b"ab", 3).unwrap();
+        assert_eq!(s.open_compressed(b"z", &framed, 16).unwrap(), b"ab");
+    }
+
+    #[cfg(feature = "compress")]
+    #[test]
+    fn compressed_expansion_is_capped() {
+        let s = session_from_psk(b"zstd");
+        let bomb = vec![0u8; 10 << 20];
+        let framed = s.seal_compressed(b"z", &bomb, 3).unwrap();
+        assert!(framed.len() < 4096);
+        assert!(matches!(
+            s.open_compressed(b"z", &framed, 1 << 20),
+            Err(Error::DecompressLimit(limit)) if limit == 1 << 20
+        ));
+    }
+
+    #[cfg(feature = "x25519")]
+    #[test]
+    fn handshake_both_sides_share_session() {
+        use handshake::{handshake_initiator, handshake_responder};
+
+        let (initiator, msg1) = handshake_initiator();
+        let (msg2, responder_sess) = handshake_responder(&msg1).unwrap();
+        let initiator_sess = initiator.finish(&msg2).unwrap();
+
+        let ct = initiator_sess.seal(b"hs", b"ping").unwrap();
+        assert_eq!(responder_sess.open(b"hs", &ct).unwrap(), b"ping");
+        let ct = responder_sess.seal(b"hs", b"pong").unwrap();
+        assert_eq!(initiator_sess.open(b"hs", &ct).unwrap(), b"pong");
+    }
+
+    #[cfg(feature = "x25519")]
+    #[test]
+    fn key_exchange_derives_identical_sessions() {
+        use handshake::KeyExchange;
+
+        let (a, b) = (KeyExchange::generate(), KeyExchange::generate());
+        let (a_pub, b_pub) = (a.public_key(), b.public_key());
+        let a_sess = a.into_session(&b_pub).unwrap();
+        let b_sess = b.into_session(&a_pub).unwrap();
+        assert_eq!(*a_sess.key, *b_sess.key);
+        let ct = a_sess.seal(b"kx", b"hello").unwrap();
+        assert_eq!(b_sess.open(b"kx", &ct).unwrap(), b"hello");
+    }
+
+    #[cfg(feature = "x25519")]
+    #[test]
+    fn key_exchange_with_tampered_peer_key_fails() {
+        use handshake::KeyExchange;
+
+        let (a, b) = (KeyExchange::generate(), KeyExchange::generate());
+        let (a_pub, mut b_pub) = (a.public_key(), b.public_key());
+        b_pub[0] ^= 0x01;
+        let a_sess = a.into_session(&b_pub).unwrap();
+        let b_sess = b.into_session(&a_pub).unwrap();
+        let ct = a_sess.seal(b"kx", b"hello").unwrap();
+        assert!(matches!(b_sess.open(b"kx", &ct), Err(Error::AuthFailed)));
+
+        // A low-order point (here: zero) can't force a predictable key.
+        assert!(matches!(
+            KeyExchange::generate().into_session(&[0u8; 32]),
+            Err(Error::Handshake)
+        ));
+    }
+
+    #[cfg(feature = "ed25519")]
+    #[test]
+    fn signed_frames_verify_only_with_matching_signer() {
+        use signing::SigningSession;
+
+        let alice = SigningSession::generate(session_from_psk(b"shared"));
+        let bob = SigningSession::generate(session_from_psk(b"shared"));
+        let mallory = SigningSession::generate(session_from_psk(b"shared"));
+
+        let framed = alice.seal_signed(b"sig", b"track 42").unwrap();
+        let alice_pub = alice.verifying_key();
+        assert_eq!(
+            bob.open_signed(&alice_pub, b"sig", &framed).unwrap(),
+            b"track 42"
+        );
+        assert!(matches!(
+            bob.open_signed(&mallory.verifying_key(), b"sig", &framed),
+            Err(Error::BadSignature)
+        ));
+
+        let forged = mallory.seal_signed(b"sig", b"track 42").unwrap();
+        assert!(matches!(
+            bob.open_signed(&alice_pub, b"sig", &forged),
+            Err(Error::BadSignature)
+        ));
+        assert!(matches!(
+            bob.open_signed(&alice_pub, b"sig", &framed[..10]),
+            Err(Error::BadSignature)
+        ));
+    }
+
+    #[test]
+    fn detached_roundtrip_matches_attached_frame() {
+        let s = Session::from_key([6u8; 32]);
+        let nonce = [9u8; 12];
+        let (ct, tag) = s.seal_detached(&nonce, b"det", b"track 42").unwrap();
+        assert_eq!(ct.len(), 8);
+        assert_eq!(
+            s.open_detached(&nonce, b"det", &ct, &tag).unwrap(),
+            b"track 42"
+        );
+        // Same bytes as an attached frame, just split differently.
+        let attached = s.seal_with_nonce(&nonce, b"det", b"track 42").unwrap();
+        assert_eq!(attached, [&nonce[..], &ct, &tag].concat());
+        assert!(matches!(
+            s.seal_detached(&[0u8; 24], b"det", b"x"),
+            Err(Error::Encrypt)
+        ));
+    }
+
+    #[test]
+    fn detached_flipped_tag_bit_fails_auth() {
+        let s = Session::from_key_with(Algorithm::XChaCha20Poly1305, [6u8; 32]);
+        let nonce = [3u8; 24];
+        let (ct, mut tag) = s.seal_detached(&nonce, b"", b"payload").unwrap();
+        tag[15] ^= 0x01;
+        assert!(matches!(
+            s.open_detached(&nonce, b"", &ct, &tag),
+            Err(Error::AuthFailed)
+        ));
+    }
+
+    #[test]
+    fn stream_roundtrips_large_payload() {
+        let s = Session::from_key([8u8; 32]);
+        let input: Vec<u8> = (0..10 * 1024 * 1024u32).map(|i| (i % 251) as u8).collect();
+        let mut sealer = SealStream::new(&s, b"blob");
+        let frames: Vec<Vec<u8>> = input
+            .chunks(64 * 1024)
+            .map(|c| sealer.push(c).unwrap())
+            .collect();
+        let prefix = sealer.nonce_prefix().to_vec();
+        let last = sealer.finish().unwrap();
+
+        let mut opener = OpenStream::new(&s, &prefix, b"blob").unwrap();
+        let mut output = Vec::with_capacity(input.len());
+        for frame in &frames {
+            output.extend(opener.push(frame).unwrap());
+        }
+        opener.finish(&last).unwrap();
+        assert_eq!(output, input);
+    }
+
+    #[test]
+    fn stream_rejects_reordered_and_truncated_streams() {
+        let s = Session::from_key_with(Algorithm::XChaCha20Poly1305, [8u8; 32]);
+        let mut sealer = SealStream::new(&s, b"");
+        let (a, b) = (sealer.push(b"a").unwrap(), sealer.push(b"b").unwrap());
+        let prefix = sealer.nonce_prefix().to_vec();
+        assert_eq!(prefix.len(), 19);
+
+        let mut opener = OpenStream::new(&s, &prefix, b"").unwrap();
+        assert!(matches!(opener.push(&b), Err(Error::AuthFailed)));
+        assert_eq!(opener.push(&a).unwrap(), b"a");
+        // Stopping early: a middle chunk is not accepted as the last one.
+        assert!(matches!(opener.finish(&b), Err(Error::AuthFailed)));
+    }
+
+    #[test]
+    fn framed_header_is_validated() {
+        let s = Session::from_key([5u8; 32]);
+        let framed = s.seal_framed(b"hdr", b"track 42").unwrap();
+        assert_eq!(&framed[..6], b"AE2E\x01\x01");
+        assert_eq!(s.open_framed(b"hdr", &framed).unwrap(), b"track 42");
+        // The header is authenticated, and raw `open` doesn't accept framed output.
+        assert!(matches!(
+            s.open(b"hdr", &framed[6..]),
+            Err(Error::AuthFailed)
+        ));
+
+        let mut bad_magic = framed.clone();
+        bad_magic[0] ^= 1;
+        assert!(matches!(
+            s.open_framed(b"hdr", &bad_magic),
+            Err(Error::InvalidHeader)
+        ));
+        assert!(matches!(
+            s.open_framed(b"hdr", b"AE2"),
+            Err(Error::InvalidHeader)
+        ));
+
+        let mut unknown_alg = framed.clone();
+        unknown_alg[5] = 0xEE;
+        assert!(matches!(
+            s.open_framed(b"hdr", &unknown_alg),
+            Err(Error::UnknownAlgorithm(_))
+        ));
+        let chacha = Session::from_key_with(Algorithm::ChaCha20Poly1305, [5u8; 32]);
+        assert!(matches!(
+            chacha.open_framed(b"hdr", &framed),
+            Err(Error::InvalidHeader)
+        ));
+        for alg in [
+            Algorithm::Aes256Gcm,
+            Algorithm::Aes128Gcm,
+            Algorithm::ChaCha20Poly1305,
+            Algorithm::XChaCha20Poly1305,
+        ] {
+            assert_eq!(Algorithm::from_id(alg.id()).unwrap(), alg);
+        }
+    }
+
+    #[test]
+    fn framed_rejects_future_versions() {
+        let s = Session::from_key([5u8; 32]);
+        let mut framed = s.seal_framed(b"", b"x").unwrap();
+        framed[4] = 2;
+        assert!(matches!(
+            s.open_framed(b"", &framed),
+            Err(Error::UnsupportedVersion(2))
+        ));
+    }
+
+    #[cfg(feature = "ring-backend")]
+    #[test]
+    fn ring_and_rustcrypto_frames_interoperate() {
+        let key = [0x24u8; 32];
+        let nonce = [7u8; 12];
+        let aes = Aes256Gcm::new(&key.into());
+        let chacha = ChaCha20Poly1305::new(&key.into());
+        let rustcrypto = |alg: Algorithm, payload: Payload<'_, '_>| {
+            let ct = match alg {
+                Algorithm::Aes256Gcm => aes.encrypt(Nonce::from_slice(&nonce), payload),
+                _ => chacha.encrypt(Nonce::from_slice(&nonce), payload),
+            };
+            [&nonce[..], &ct.unwrap()].concat()
+        };
+
+        for alg in [Algorithm::Aes256Gcm, Algorithm::ChaCha20Poly1305] {
+            let s = Session::from_key_with(alg, key);
+            // ring seals, RustCrypto's frame for the same inputs is byte-identical ...
+            let ring_frame = s.seal_with_nonce(&nonce, b"aad", b"track 42").unwrap();
+            let expected = rustcrypto(
+                alg,
+                Payload {
+                    msg: b"track 42",
+                    aad: b"aad",
+                },
+            );
+            assert_eq!(ring_frame, expected, "{alg:?}");
+            // ... and ring opens what RustCrypto sealed.
+            assert_eq!(s.open(b"aad", &expected).unwrap(), b"track 42");
+            let mut in_place = expected.clone();
+            s.open_in_place(b"aad", &mut in_place).unwrap();
+            assert_eq!(in_place, b"track 42");
+        }
+    }
+
+    #[cfg(feature = "rustls")]
+    #[test]
+    fn ekm_label_separates_sessions() {
+        use tls::{session_from_ekm, session_from_ekm_default, KeyingMaterialExporter};
+
+        /// Deterministic stand-in for a completed TLS connection.
+        struct StubExporter;
+
+        impl KeyingMaterialExporter for StubExporter {
+            fn export_keying_material(
+                &self,
+                out: &mut [u8],
+                label: &[u8],
+                context: Option<&[u8]>,
+            ) -> Result<(), rustls::Error> {
+                let mut h = blake3::Hasher::new_keyed(&[7u8; 32]);
+                h.update(&bind_aad(&[label, context.unwrap_or_default()]));
+                h.finalize_xof().fill(out);
+                Ok(())
+            }
+        }
+
+        let v1 = session_from_ekm(&StubExporter, b"ads-e2ee-v1", None).unwrap();
+        let v2 = session_from_ekm(&StubExporter, b"ads-e2ee-v2", None).unwrap();
+        assert_ne!(*v1.key, *v2.key);
+        let again = session_from_ekm(&StubExporter, b"ads-e2ee-v1", None).unwrap();
+        assert_eq!(*v1.key, *again.key);
+
+        let default = session_from_ekm_default(&StubExporter).unwrap();
+        let explicit = session_from_ekm(&StubExporter, tls::DEFAULT_LABEL, Some(&[])).unwrap();
+        assert_eq!(*default.key, *explicit.key);
+        assert!(v1.open(b"", &v2.seal(b"", b"x").unwrap()).is_err());
     }
 }
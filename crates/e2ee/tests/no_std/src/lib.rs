@@ -0,0 +1,18 @@
+//! Exercises the `no_std` surface of `e2ee` with a caller-supplied RNG.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use e2ee::rand_core::{CryptoRng, RngCore};
+use e2ee::{Error, Session};
+
+pub fn roundtrip<R>(rng: &mut R, key: [u8; 32], msg: &[u8]) -> Result<Vec<u8>, Error>
+where
+    R: RngCore + CryptoRng,
+{
+    let session = Session::from_key(key);
+    let framed = session.seal_with_rng(rng, b"no_std", msg)?;
+    session.open(b"no_std", &framed)
+}
@@ -0,0 +1,82 @@
+//! `tls::connect` against a real rustls server on localhost with a self-signed certificate.
+#![cfg(feature = "rustls")]
+
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread;
+
+use e2ee::tls::{connect, session_from_ekm_default};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+use rustls::{RootCertStore, ServerConfig, ServerConnection};
+
+fn provider() -> Arc<rustls::crypto::CryptoProvider> {
+    Arc::new(rustls::crypto::aws_lc_rs::default_provider())
+}
+
+#[test]
+fn client_and_server_derive_the_same_session() {
+    let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    let cert_der = CertificateDer::from(cert.cert.der().to_vec());
+    let key_der = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(cert.key_pair.serialize_der()));
+    let server_config = ServerConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_no_client_auth()
+        .with_single_cert(vec![cert_der.clone()], key_der)
+        .unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = thread::spawn(move || {
+        let (mut tcp, _) = listener.accept().unwrap();
+        let mut conn = ServerConnection::new(Arc::new(server_config)).unwrap();
+        while conn.is_handshaking() {
+            conn.complete_io(&mut tcp).unwrap();
+        }
+        session_from_ekm_default(&conn).unwrap()
+    });
+
+    let mut roots = RootCertStore::empty();
+    roots.add(cert_der).unwrap();
+    let (_stream, client) = connect("localhost", TcpStream::connect(addr).unwrap(), roots).unwrap();
+    let server = server.join().unwrap();
+
+    assert_eq!(client.key_id(), server.key_id());
+    let framed = client.seal(b"tls", b"track 42").unwrap();
+    assert_eq!(server.open(b"tls", &framed).unwrap(), b"track 42");
+}
+
+#[test]
+fn connect_rejects_untrusted_server() {
+    let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    let key_der = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(cert.key_pair.serialize_der()));
+    let server_config = ServerConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_no_client_auth()
+        .with_single_cert(
+            vec![CertificateDer::from(cert.cert.der().to_vec())],
+            key_der,
+        )
+        .unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = thread::spawn(move || {
+        let (mut tcp, _) = listener.accept().unwrap();
+        let mut conn = ServerConnection::new(Arc::new(server_config)).unwrap();
+        while conn.is_handshaking() {
+            if conn.complete_io(&mut tcp).is_err() {
+                break;
+            }
+        }
+    });
+
+    let result = connect(
+        "localhost",
+        TcpStream::connect(addr).unwrap(),
+        RootCertStore::empty(),
+    );
+    assert!(matches!(result, Err(e2ee::Error::Handshake)));
+    server.join().unwrap();
+}
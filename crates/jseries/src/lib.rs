@@ -1,19 +1,127 @@
 //! Link 16 J-Series parsing/serialization (prototype)
 //! This is a simplified, non-authoritative representation suitable for scaffolding.
+//!
+//! The `serde` feature derives `Serialize`/`Deserialize` for [`JMessage`] and the body types.
+//! Messages are tagged by kind (`{"type": "J3_2", ...}`) and fields keep their packed
+//! integer scaling; use the `lat_deg`/`lon_deg`/`alt_m` accessors or
+//! [`J3_2AirTrack::to_geo`] for human units.
+//!
+//! The `geojson` feature adds `J3_2AirTrack::to_geojson` for quick map visualisation, and
+//! `tokio` adds `JMessageReader` for reading `to_frame` records off an `AsyncRead`, and
+//! `pcap` adds `PcapWriter` for recording emitted frames. `chrono` converts the J3.2 v3
+//! time of day to and from `chrono::NaiveTime`.
 
 use core::fmt;
 use deku::prelude::*;
 use std::fmt::Formatter;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "pcap")]
+mod pcap;
+#[cfg(feature = "tokio")]
+mod reader;
+mod registry;
+pub mod units;
+#[cfg(feature = "pcap")]
+pub use pcap::PcapWriter;
+#[cfg(feature = "tokio")]
+pub use reader::JMessageReader;
+pub use registry::{JBody, Registry};
 
 pub const MSG_ID_J3_2: u8 = 0x32; // Prototype identifier for J3.2 Air Track
+pub const MSG_ID_J3_2_V2: u8 = 0xB2; // Prototype identifier for J3.2 v2 (adds identity)
+pub const MSG_ID_J3_2_V3: u8 = 0xB3; // Prototype identifier for J3.2 v3 (adds time of day)
+pub const MSG_ID_J2_2: u8 = 0x22; // Prototype identifier for J2.2 PPLI
+pub const MSG_ID_J3_3: u8 = 0x33; // Prototype identifier for J3.3 Surface Track
+pub const MSG_ID_J7_0: u8 = 0x70; // Prototype identifier for J7.0 Track Management
+/// Schema versions understood by the versioned framing (`to_bytes_v2`/`from_bytes_v2`).
+pub const SCHEMA_V1: u8 = 1; // J3.2 base layout; the only J2.2/J3.3/J7.0 layout
+pub const SCHEMA_V2: u8 = 2; // J3.2 with identity
+pub const SCHEMA_V3: u8 = 3; // J3.2 with identity and time of day
 const LAT_SCALE: f64 = 524287.0 / 180.0; // 19-bit mapping for -90 to +90
 const LON_SCALE: f64 = 524287.0 / 360.0; // 19-bit mapping for -180 to +180
 const ALT_STEP: f64 = 25.0; // Standard 25ft altitude increments
+const ALT_MAX: u16 = (1 << 14) - 1; // Largest 14-bit altitude field value
+const E7: f64 = 1e7; // Fixed-point scale for the J3.3 lat/lon fields
+const METERS_PER_DEG_LAT: f64 = 111_320.0; // Mean length of one degree of latitude
+
+/// Positional precision applied by [`J3_2AirTrack::from_geo_with_precision`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Precision {
+    /// Full precision of the packed fields.
+    #[default]
+    Full,
+    /// Snap lat/lon to the centre of a grid cell roughly `meters` on a side before packing,
+    /// so positions can't be recovered more precisely than the grid (privacy mode).
+    Grid { meters: f64 },
+}
+
+impl Precision {
+    /// Apply the precision to a position, returning the (possibly snapped) lat/lon.
+    pub fn apply(self, lat_deg: f64, lon_deg: f64) -> (f64, f64) {
+        match self {
+            Precision::Full => (lat_deg, lon_deg),
+            Precision::Grid { meters } if meters > 0.0 => {
+                let lat_step = meters / METERS_PER_DEG_LAT;
+                let lat = snap(lat_deg, lat_step).clamp(-90.0, 90.0);
+                // Widen longitude cells toward the poles so they stay ~`meters` wide.
+                let lon_step = (lat_step / lat.to_radians().cos().max(1e-6)).min(360.0);
+                let lon = snap(lon_deg, lon_step).clamp(-180.0, 180.0);
+                (lat, lon)
+            }
+            Precision::Grid { .. } => (lat_deg, lon_deg),
+        }
+    }
+}
+
+fn snap(value: f64, step: f64) -> f64 {
+    ((value / step).floor() + 0.5) * step
+}
+/// Errors from encoding and decoding J-Series messages. New variants may be added in minor
+/// releases, so matches outside this crate need a wildcard arm.
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub enum Error {
     Unsupported(u8),
     Short(usize),
     Deku(String),
+    InvalidIdentity(u8),
+    UnsupportedSchema {
+        version: u8,
+    },
+    OutOfRange {
+        field: &'static str,
+        value: f64,
+    },
+    ExceedsLimit {
+        len: usize,
+        max: usize,
+    },
+    /// The frame starting at byte `offset` of a stream needs `needed` bytes but the buffer
+    /// ends first.
+    ShortAt {
+        offset: usize,
+        needed: usize,
+    },
+    /// The body of the frame starting at byte `offset` of a stream failed to decode.
+    DekuAt {
+        offset: usize,
+        message: String,
+    },
+    /// The CRC-32C trailer of a `to_bytes_crc` frame doesn't match its contents.
+    BadCrc {
+        expected: u32,
+        actual: u32,
+    },
+    /// Reading the underlying stream failed.
+    Io(String),
+    /// A required builder field was never set.
+    MissingField(&'static str),
+    /// A message in a concatenated stream failed to parse at byte `offset`.
+    At {
+        offset: usize,
+        error: Box<Error>,
+    },
 }
 
 impl From<DekuError> for Error {
@@ -28,37 +136,387 @@ impl fmt::Display for Error {
             Error::Unsupported(k) => write!(f, "unsupported message kind: {k:02x}"),
             Error::Short(n) => write!(f, "buffer too short: {n} bytes"),
             Error::Deku(s) => write!(f, "deku error: {s}"),
+            Error::InvalidIdentity(c) => write!(f, "invalid identity code: {c}"),
+            Error::UnsupportedSchema { version } => {
+                write!(f, "unsupported schema version: {version}")
+            }
+            Error::OutOfRange { field, value } => write!(f, "{field} out of range: {value}"),
+            Error::ExceedsLimit { len, max } => {
+                write!(f, "message body of {len} bytes exceeds limit of {max}")
+            }
+            Error::ShortAt { offset, needed } => {
+                write!(f, "frame at byte {offset} truncated: needs {needed} bytes")
+            }
+            Error::DekuAt { offset, message } => {
+                write!(f, "deku error in frame at byte {offset}: {message}")
+            }
+            Error::BadCrc { expected, actual } => {
+                write!(
+                    f,
+                    "crc mismatch: trailer {expected:08x}, computed {actual:08x}"
+                )
+            }
+            Error::Io(e) => write!(f, "io error: {e}"),
+            Error::MissingField(field) => write!(f, "missing required field: {field}"),
+            Error::At { offset, error } => write!(f, "at byte {offset}: {error}"),
         }
     }
 }
 
 impl std::error::Error for Error {}
 
+impl Error {
+    /// Attach the offset of the stream frame this error came from.
+    fn at(self, offset: usize) -> Self {
+        match self {
+            Error::ShortAt { .. } | Error::DekuAt { .. } | Error::At { .. } => self,
+            Error::Deku(message) => Error::DekuAt { offset, message },
+            error => Error::At {
+                offset,
+                error: Box::new(error),
+            },
+        }
+    }
+}
+
+/// A decoded J-Series message. Message kinds are added often, so the enum is
+/// `#[non_exhaustive]`: matches outside this crate need a wildcard arm.
+///
+/// ```
+/// use jseries::JMessage;
+///
+/// fn track_number(msg: &JMessage) -> Option<u16> {
+///     match msg {
+///         JMessage::J3_2(t) => Some(t.track_number),
+///         JMessage::J3_2V2(v) => Some(v.base.track_number),
+///         _ => None,
+///     }
+/// }
+/// ```
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type"))]
+#[non_exhaustive]
 pub enum JMessage {
     J3_2(J3_2AirTrack),
+    J3_2V2(J3_2AirTrackV2),
+    J3_2V3(J3_2AirTrackV3),
+    J2_2(J2_2Ppli),
+    J3_3(J3_3SurfaceTrack),
+    J7_0(J7_0TrackManagement),
 }
 
 impl fmt::Display for JMessage {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
-            JMessage::J3_2(track) => write!(f, "J3.2 Message: {}", track),
+            JMessage::J3_2(track) => write!(f, "J3.2 AirTrack: {}", track),
+            JMessage::J3_2V2(v) => write!(f, "J3.2v2 AirTrack: {} ({})", v.base, v.identity()),
+            JMessage::J3_2V3(v) => write!(
+                f,
+                "J3.2v3 AirTrack: {} ({}) at {}s",
+                v.base.base,
+                v.base.identity(),
+                v.time_of_day
+            ),
+            JMessage::J2_2(p) => write!(f, "J2.2 PPLI: {}", p),
+            JMessage::J3_3(t) => write!(f, "J3.3 SurfaceTrack: {}", t),
+            JMessage::J7_0(m) => write!(f, "J7.0 TrackManagement: {}", m),
+        }
+    }
+}
+
+/// Semantic grouping of message kinds, so routers don't need to match every id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MessageCategory {
+    PositionReport,
+    Control,
+    Weather,
+    Unknown,
+}
+
+impl MessageCategory {
+    /// Category of a raw message id; ids this crate doesn't know map to `Unknown`.
+    pub fn from_id(kind: u8) -> Self {
+        match kind {
+            MSG_ID_J3_2 | MSG_ID_J3_2_V2 | MSG_ID_J3_2_V3 | MSG_ID_J2_2 | MSG_ID_J3_3 => {
+                MessageCategory::PositionReport
+            }
+            MSG_ID_J7_0 => MessageCategory::Control,
+            _ => MessageCategory::Unknown,
         }
     }
 }
 
 impl JMessage {
+    /// Message id byte written by `to_bytes`.
+    pub fn id(&self) -> u8 {
+        match self {
+            JMessage::J3_2(_) => MSG_ID_J3_2,
+            JMessage::J3_2V2(_) => MSG_ID_J3_2_V2,
+            JMessage::J3_2V3(_) => MSG_ID_J3_2_V3,
+            JMessage::J2_2(_) => MSG_ID_J2_2,
+            JMessage::J3_3(_) => MSG_ID_J3_3,
+            JMessage::J7_0(_) => MSG_ID_J7_0,
+        }
+    }
+
+    pub fn category(&self) -> MessageCategory {
+        MessageCategory::from_id(self.id())
+    }
+
+    /// Encoded body length (excluding the id byte) of message kind `kind`, or `None` for
+    /// an unknown kind. Every kind is fixed-width, which is what lets a stream of
+    /// concatenated messages be split without a length prefix.
+    pub const fn body_len(kind: u8) -> Option<usize> {
+        match kind {
+            MSG_ID_J3_2 | MSG_ID_J3_2_V2 => Some(J3_2AirTrack::BODY_LEN),
+            MSG_ID_J3_2_V3 => Some(J3_2AirTrackV3::BODY_LEN),
+            MSG_ID_J2_2 => Some(J2_2Ppli::BODY_LEN),
+            MSG_ID_J3_3 => Some(J3_3SurfaceTrack::BODY_LEN),
+            MSG_ID_J7_0 => Some(J7_0TrackManagement::BODY_LEN),
+            _ => None,
+        }
+    }
+
+    /// Parse every message in `input`, which holds back-to-back `to_bytes` frames.
+    /// Errors carry the offset of the frame that failed: [`Error::ShortAt`] for a truncated
+    /// frame, [`Error::DekuAt`] for an undecodable body, [`Error::At`] otherwise.
+    pub fn parse_stream(input: &[u8]) -> Result<Vec<Self>, Error> {
+        JMessageIter::new(input).collect()
+    }
+
+    /// Like `parse_stream`, but skips corrupt frames instead of stopping: returns every
+    /// message that decoded plus `(offset, error)` for each bad region.
+    ///
+    /// Resync heuristic: after a failure at `offset`, scan forward byte by byte for the next
+    /// position where a known message id starts a frame that decodes, and resume there.
+    /// The bytes skipped are reported once, as the error at `offset`. A corrupt region that
+    /// happens to contain a decodable frame resyncs onto it, so this can yield spurious
+    /// messages; use `to_frame`/`to_bytes_crc` framing where that matters.
+    pub fn parse_all_lenient(input: &[u8]) -> (Vec<Self>, Vec<(usize, Error)>) {
+        let decode_at = |offset: usize| Self::from_bytes_consumed(&input[offset..]);
+        let (mut messages, mut errors) = (Vec::new(), Vec::new());
+        let mut offset = 0;
+        while offset < input.len() {
+            match decode_at(offset) {
+                Ok((msg, len)) => {
+                    messages.push(msg);
+                    offset += len;
+                }
+                Err(error) => {
+                    errors.push((offset, error));
+                    offset = (offset + 1..input.len())
+                        .find(|&i| Self::body_len(input[i]).is_some() && decode_at(i).is_ok())
+                        .unwrap_or(input.len());
+                }
+            }
+        }
+        (messages, errors)
+    }
+
+    /// Decode one message; trailing bytes after it are ignored.
     pub fn from_bytes(input: &[u8]) -> Result<Self, Error> {
-        if input.is_empty() {
+        Self::from_bytes_consumed(input).map(|(msg, _)| msg)
+    }
+
+    /// Decode one message and return it with the number of bytes it consumed (id byte
+    /// included), so the caller can continue from `&input[consumed..]`. Dispatch goes
+    /// through [`Registry::global`].
+    pub fn from_bytes_consumed(input: &[u8]) -> Result<(Self, usize), Error> {
+        Registry::global().decode(input)
+    }
+
+    /// Like `from_bytes`, but refuses a body longer than `max_len` bytes before decoding it.
+    ///
+    /// Bodies are fixed-width today, so the claimed length is everything after the id byte;
+    /// use this on untrusted input so a future length-prefixed kind can't force a huge read.
+    pub fn from_bytes_bounded(input: &[u8], max_len: usize) -> Result<Self, Error> {
+        let len = input.len().saturating_sub(1);
+        if len > max_len {
+            return Err(Error::ExceedsLimit { len, max: max_len });
+        }
+        Self::from_bytes(input)
+    }
+
+    /// Like `from_bytes`, but rejects unknown identity codes instead of reading them as
+    /// [`Identity::Pending`].
+    pub fn from_bytes_strict(input: &[u8]) -> Result<Self, Error> {
+        let msg = Self::from_bytes(input)?;
+        let code = match &msg {
+            JMessage::J3_2V2(v) => Some(v.identity_code),
+            JMessage::J3_2V3(v) => Some(v.base.identity_code),
+            _ => None,
+        };
+        if let Some(code) = code {
+            Identity::from_code(code).ok_or(Error::InvalidIdentity(code))?;
+        }
+        Ok(msg)
+    }
+
+    /// Like `from_bytes`, but also rejects physically impossible positions and headings
+    /// with `Error::OutOfRange` (see [`JMessage::validate`]).
+    pub fn from_bytes_validated(input: &[u8]) -> Result<Self, Error> {
+        let msg = Self::from_bytes(input)?;
+        msg.validate()?;
+        Ok(msg)
+    }
+
+    /// Range-check the decoded body. Kinds without geographic fields always pass.
+    pub fn validate(&self) -> Result<(), Error> {
+        match self {
+            JMessage::J3_2(t) => t.validate(),
+            JMessage::J3_2V2(v) => v.base.validate(),
+            JMessage::J3_2V3(v) => v.base.base.validate(),
+            JMessage::J3_3(t) => t.validate(),
+            JMessage::J2_2(_) | JMessage::J7_0(_) => Ok(()),
+        }
+    }
+
+    /// Encode as a length-delimited frame for byte streams: `[u16 big-endian length]`
+    /// followed by the `to_bytes` encoding (id byte included), the same record layout the
+    /// bridge batches into PDUs.
+    pub fn to_frame(&self) -> Result<Vec<u8>, Error> {
+        let msg = self.to_bytes()?;
+        let mut out = Vec::with_capacity(2 + msg.len());
+        push_frame(&mut out, &msg)?;
+        Ok(out)
+    }
+
+    /// Decode one `to_frame` record from the front of `input`, returning the message and the
+    /// bytes the whole record occupies (prefix included). A declared length running past the
+    /// end of `input` is `Error::Short`.
+    pub fn from_frame(input: &[u8]) -> Result<(Self, usize), Error> {
+        let [hi, lo, ..] = *input else {
             return Err(Error::Short(input.len()));
+        };
+        let end = 2 + usize::from(u16::from_be_bytes([hi, lo]));
+        let record = input.get(2..end).ok_or(Error::Short(input.len()))?;
+        Ok((Self::from_bytes(record)?, end))
+    }
+
+    /// `to_bytes` followed by a 4-byte big-endian CRC-32C (Castagnoli) of those bytes, for
+    /// links without an authenticated layer underneath.
+    pub fn to_bytes_crc(&self) -> Result<Vec<u8>, Error> {
+        let mut out = self.to_bytes()?;
+        let crc = crc32c(&out);
+        out.extend(crc.to_be_bytes());
+        Ok(out)
+    }
+
+    /// Verify and strip the `to_bytes_crc` trailer, then decode. A mismatch is
+    /// `Error::BadCrc`; nothing is decoded from a corrupted frame.
+    pub fn from_bytes_crc(input: &[u8]) -> Result<Self, Error> {
+        let split = input
+            .len()
+            .checked_sub(4)
+            .ok_or(Error::Short(input.len()))?;
+        let (msg, trailer) = input.split_at(split);
+        let expected = u32::from_be_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+        let actual = crc32c(msg);
+        if expected != actual {
+            return Err(Error::BadCrc { expected, actual });
         }
-        let kind = input[0];
-        match kind {
-            MSG_ID_J3_2 => {
-                // remaining is the body
-                let (_, body) = J3_2AirTrack::from_bytes((&input[1..], 0))?;
+        Self::from_bytes(msg)
+    }
+
+    /// Decode the little-endian layout some legacy subsystems use: same id byte and field
+    /// widths as `from_bytes`, with multi-byte fields little-endian.
+    pub fn from_bytes_le(input: &[u8]) -> Result<Self, Error> {
+        use deku::ctx::Endian::Little;
+        let (&kind, body) = input.split_first().ok_or(Error::Short(input.len()))?;
+        Ok(match kind {
+            MSG_ID_J3_2 => JMessage::J3_2(read_body_with(body, Little)?),
+            MSG_ID_J3_2_V2 => JMessage::J3_2V2(read_body_with(body, Little)?),
+            MSG_ID_J3_2_V3 => JMessage::J3_2V3(read_body_with(body, Little)?),
+            MSG_ID_J2_2 => JMessage::J2_2(read_body_with(body, Little)?),
+            MSG_ID_J3_3 => JMessage::J3_3(read_body_with(body, Little)?),
+            MSG_ID_J7_0 => JMessage::J7_0(read_body_with(body, Little)?),
+            other => return Err(Error::Unsupported(other)),
+        })
+    }
+
+    /// Encode with little-endian fields; the inverse of `from_bytes_le`.
+    pub fn to_bytes_le(&self) -> Result<Vec<u8>, Error> {
+        use deku::ctx::Endian::Little;
+        let body = match self {
+            JMessage::J3_2(v) => write_body_with(v, Little)?,
+            JMessage::J3_2V2(v) => write_body_with(v, Little)?,
+            JMessage::J3_2V3(v) => write_body_with(v, Little)?,
+            JMessage::J2_2(v) => write_body_with(v, Little)?,
+            JMessage::J3_3(v) => write_body_with(v, Little)?,
+            JMessage::J7_0(v) => write_body_with(v, Little)?,
+        };
+        let mut out = Vec::with_capacity(1 + body.len());
+        out.push(self.id());
+        out.extend(body);
+        Ok(out)
+    }
+
+    /// Decode the versioned framing: `[msg id, schema version, body..]`.
+    /// Versions newer than this decoder understands are rejected rather than misparsed.
+    pub fn from_bytes_v2(input: &[u8]) -> Result<Self, Error> {
+        if input.len() < 2 {
+            return Err(Error::Short(input.len()));
+        }
+        let (kind, version, body) = (input[0], input[1], &input[2..]);
+        match (kind, version) {
+            (MSG_ID_J3_2, SCHEMA_V1) => {
+                let (_, body) = J3_2AirTrack::from_bytes((body, 0))?;
                 Ok(JMessage::J3_2(body))
             }
+            (MSG_ID_J3_2, SCHEMA_V2) => {
+                let (_, body) = J3_2AirTrackV2::from_bytes((body, 0))?;
+                Ok(JMessage::J3_2V2(body))
+            }
+            (MSG_ID_J3_2, SCHEMA_V3) => {
+                let (_, body) = J3_2AirTrackV3::from_bytes((body, 0))?;
+                Ok(JMessage::J3_2V3(body))
+            }
+            (MSG_ID_J3_2, version) => Err(Error::UnsupportedSchema { version }),
+            (MSG_ID_J2_2, SCHEMA_V1) => {
+                let (_, body) = J2_2Ppli::from_bytes((body, 0))?;
+                Ok(JMessage::J2_2(body))
+            }
+            (MSG_ID_J2_2, version) => Err(Error::UnsupportedSchema { version }),
+            (MSG_ID_J3_3, SCHEMA_V1) => {
+                let (_, body) = J3_3SurfaceTrack::from_bytes((body, 0))?;
+                Ok(JMessage::J3_3(body))
+            }
+            (MSG_ID_J3_3, version) => Err(Error::UnsupportedSchema { version }),
+            (MSG_ID_J7_0, SCHEMA_V1) => {
+                let (_, body) = J7_0TrackManagement::from_bytes((body, 0))?;
+                Ok(JMessage::J7_0(body))
+            }
+            (MSG_ID_J7_0, version) => Err(Error::UnsupportedSchema { version }),
+            (other, _) => Err(Error::Unsupported(other)),
+        }
+    }
+
+    /// Encode with the versioned framing; the schema version follows from the variant.
+    pub fn to_bytes_v2(&self) -> Result<Vec<u8>, Error> {
+        let (kind, version, body) = match self {
+            JMessage::J3_2(v) => (MSG_ID_J3_2, SCHEMA_V1, v.to_bytes()?),
+            JMessage::J3_2V2(v) => (MSG_ID_J3_2, SCHEMA_V2, v.to_bytes()?),
+            JMessage::J3_2V3(v) => (MSG_ID_J3_2, SCHEMA_V3, v.to_bytes()?),
+            JMessage::J2_2(v) => (MSG_ID_J2_2, SCHEMA_V1, v.to_bytes()?),
+            JMessage::J3_3(v) => (MSG_ID_J3_3, SCHEMA_V1, v.to_bytes()?),
+            JMessage::J7_0(v) => (MSG_ID_J7_0, SCHEMA_V1, v.to_bytes()?),
+        };
+        let mut out = Vec::with_capacity(2 + body.len());
+        out.extend([kind, version]);
+        out.extend(body);
+        Ok(out)
+    }
+
+    /// Decode a borrowed view over `input` without copying the body. Only J3.2 and J3.2 v2
+    /// have a borrowed view; other ids are `Error::Unsupported`.
+    pub fn from_bytes_ref(input: &[u8]) -> Result<JMessageRef<'_>, Error> {
+        if input.is_empty() {
+            return Err(Error::Short(input.len()));
+        }
+        match input[0] {
+            MSG_ID_J3_2 => Ok(JMessageRef::J3_2(J3_2AirTrackRef::new(&input[1..])?)),
+            MSG_ID_J3_2_V2 => Ok(JMessageRef::J3_2V2(J3_2AirTrackRef::new(&input[1..])?)),
             other => Err(Error::Unsupported(other)),
         }
     }
@@ -72,14 +530,184 @@ impl JMessage {
                 out.extend(body);
                 Ok(out)
             }
+            JMessage::J3_2V2(v) => {
+                let mut out = Vec::with_capacity(1 + 16);
+                out.push(MSG_ID_J3_2_V2);
+                out.extend(v.to_bytes()?);
+                Ok(out)
+            }
+            JMessage::J3_2V3(v) => {
+                let mut out = Vec::with_capacity(1 + J3_2AirTrackV3::BODY_LEN);
+                out.push(MSG_ID_J3_2_V3);
+                out.extend(v.to_bytes()?);
+                Ok(out)
+            }
+            JMessage::J2_2(v) => {
+                let mut out = Vec::with_capacity(1 + J2_2Ppli::BODY_LEN);
+                out.push(MSG_ID_J2_2);
+                out.extend(v.to_bytes()?);
+                Ok(out)
+            }
+            JMessage::J3_3(v) => {
+                let mut out = Vec::with_capacity(1 + J3_3SurfaceTrack::BODY_LEN);
+                out.push(MSG_ID_J3_3);
+                out.extend(v.to_bytes()?);
+                Ok(out)
+            }
+            JMessage::J7_0(v) => {
+                let mut out = Vec::with_capacity(1 + J7_0TrackManagement::BODY_LEN);
+                out.push(MSG_ID_J7_0);
+                out.extend(v.to_bytes()?);
+                Ok(out)
+            }
+        }
+    }
+}
+
+/// Append `record` to `out` with the `[u16 big-endian length]` prefix of
+/// [`JMessage::to_frame`]. `record` is opaque, so sealed messages can be framed too; one
+/// longer than `u16::MAX` is `Error::ExceedsLimit` and leaves `out` untouched.
+pub fn push_frame(out: &mut Vec<u8>, record: &[u8]) -> Result<(), Error> {
+    let len = u16::try_from(record.len()).map_err(|_| Error::ExceedsLimit {
+        len: record.len(),
+        max: u16::MAX as usize,
+    })?;
+    out.extend(len.to_be_bytes());
+    out.extend_from_slice(record);
+    Ok(())
+}
+
+/// Split back-to-back `push_frame` records into their payloads. A truncated prefix or
+/// record is `Error::ShortAt` with the offset of the record that ran out.
+pub fn split_frames(mut input: &[u8]) -> Result<Vec<&[u8]>, Error> {
+    let total = input.len();
+    let mut records = Vec::new();
+    while !input.is_empty() {
+        let offset = total - input.len();
+        let len = match *input {
+            [hi, lo, ..] => usize::from(u16::from_be_bytes([hi, lo])),
+            _ => return Err(Error::ShortAt { offset, needed: 2 }),
+        };
+        let record = input.get(2..2 + len).ok_or(Error::ShortAt {
+            offset,
+            needed: 2 + len,
+        })?;
+        records.push(record);
+        input = &input[2 + len..];
+    }
+    Ok(records)
+}
+
+/// Meters to the 14-bit field of 25 ft steps, rounded to the nearest step and clamped to
+/// `0..=ALT_MAX` (NaN packs as 0) so the field never overflows its bit width.
+fn pack_altitude(alt_meters: f64) -> u16 {
+    (alt_meters * 3.28084 / ALT_STEP)
+        .round()
+        .clamp(0.0, ALT_MAX as f64) as u16
+}
+
+/// Bitwise CRC-32C (reflected polynomial 0x82F63B78); frames are small enough that a table
+/// isn't worth carrying.
+fn crc32c(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, &b| {
+        (0..8).fold(crc ^ u32::from(b), |crc, _| {
+            (crc >> 1) ^ (0x82F6_3B78 & (crc & 1).wrapping_neg())
+        })
+    })
+}
+
+/// Decode a body from the front of `body`, returning it with the bytes it occupies
+/// (a partially read final byte counts as consumed).
+fn read_body<'a, T: DekuContainerRead<'a>>(body: &'a [u8]) -> Result<(T, usize), Error> {
+    let ((rest, bit), value) = T::from_bytes((body, 0))?;
+    Ok((value, body.len() - rest.len() + usize::from(bit > 0)))
+}
+
+/// Decode a body laid out with byte order `endian`.
+fn read_body_with<'a, T>(body: &'a [u8], endian: deku::ctx::Endian) -> Result<T, Error>
+where
+    T: DekuReader<'a, deku::ctx::Endian>,
+{
+    let mut cursor = std::io::Cursor::new(body);
+    let mut reader = deku::reader::Reader::new(&mut cursor);
+    Ok(T::from_reader_with_ctx(&mut reader, endian)?)
+}
+
+/// Encode a body with byte order `endian`, padding the last byte with zero bits.
+fn write_body_with<T>(value: &T, endian: deku::ctx::Endian) -> Result<Vec<u8>, Error>
+where
+    T: DekuWriter<deku::ctx::Endian>,
+{
+    let mut out = std::io::Cursor::new(Vec::new());
+    let mut writer = deku::writer::Writer::new(&mut out);
+    value.to_writer(&mut writer, endian)?;
+    writer.finalize()?;
+    Ok(out.into_inner())
+}
+
+/// Iterator over back-to-back `to_bytes` frames, advancing by each kind's
+/// [`JMessage::body_len`]. Stops after the first error.
+#[derive(Debug, Clone)]
+pub struct JMessageIter<'a> {
+    input: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> JMessageIter<'a> {
+    pub fn new(input: &'a [u8]) -> Self {
+        Self { input, offset: 0 }
+    }
+
+    /// Byte offset of the next frame.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    fn next_frame(&self) -> Result<&'a [u8], Error> {
+        let rest = &self.input[self.offset..];
+        let len = 1 + JMessage::body_len(rest[0]).ok_or(Error::Unsupported(rest[0]))?;
+        rest.get(..len).ok_or(Error::ShortAt {
+            offset: self.offset,
+            needed: len,
+        })
+    }
+}
+
+impl Iterator for JMessageIter<'_> {
+    type Item = Result<JMessage, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.offset >= self.input.len() {
+            return None;
+        }
+        let offset = self.offset;
+        match self
+            .next_frame()
+            .and_then(|frame| Ok((frame.len(), JMessage::from_bytes(frame)?)))
+        {
+            Ok((len, msg)) => {
+                self.offset += len;
+                Some(Ok(msg))
+            }
+            Err(error) => {
+                self.offset = self.input.len();
+                Some(Err(error.at(offset)))
+            }
         }
     }
 }
 
 /// Prototype J3.2 Air Track body (highly simplified)
-/// Big-endian, fixed-width layout to keep bit/byte packing explicit.
+/// Big-endian, fixed-width layout to keep bit/byte packing explicit. The body types take the
+/// byte order as deku context (defaulting to big-endian) so `JMessage::to_bytes_le` can
+/// reuse the layout.
 #[derive(Debug, Clone, PartialEq, Eq, DekuRead, DekuWrite)]
-#[deku(endian = "big")]
+#[deku(
+    endian = "endian",
+    ctx = "endian: deku::ctx::Endian",
+    ctx_default = "deku::ctx::Endian::Big"
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct J3_2AirTrack {
     #[deku(bytes = 2)]
     pub track: u16,
@@ -100,6 +728,9 @@ pub struct J3_2AirTrack {
 }
 
 impl J3_2AirTrack {
+    /// Encoded body length in bytes (117 bits, padded to a byte boundary).
+    pub const BODY_LEN: usize = 15;
+
     pub fn from_geo(
         track: u16,
         lat_deg: f64,
@@ -113,27 +744,894 @@ impl J3_2AirTrack {
             track_number: track & 0x0FFF,
             latitude: ((lat_deg + 90.0) * LAT_SCALE).round() as u32, // 19-bit squish
             longitude: ((lon_deg + 180.0) * LON_SCALE).round() as u32, // 19-bit squish
-            altitude: (alt_meters * 3.28084 / ALT_STEP).round() as u16, // 14-bit squish
+            altitude: pack_altitude(alt_meters),
+            parity: 0,
+            speed_ms,
+            heading_cdeg: heading_deg,
+        }
+    }
+
+    /// `from_geo` taking altitude in feet, speed in knots and heading in degrees, for
+    /// operator-entered values. Converts to SI and delegates to `from_geo`, which stays the
+    /// canonical path; speed saturates at the 16-bit field and heading wraps into `[0, 360)`.
+    pub fn from_geo_imperial(
+        track: u16,
+        lat_deg: f64,
+        lon_deg: f64,
+        alt_ft: f64,
+        speed_kt: f64,
+        heading_deg: f64,
+    ) -> Self {
+        Self::from_geo(
+            track,
+            lat_deg,
+            lon_deg,
+            units::ft_to_m(alt_ft),
+            units::knots_to_ms(speed_kt).round() as u16,
+            units::deg_to_cdeg(heading_deg),
+        )
+    }
+
+    /// `from_geo` with the position first reduced to `precision`.
+    pub fn from_geo_with_precision(
+        track: u16,
+        lat_deg: f64,
+        lon_deg: f64,
+        alt_meters: f64,
+        speed_ms: u16,
+        heading_deg: u16,
+        precision: Precision,
+    ) -> Self {
+        let (lat_deg, lon_deg) = precision.apply(lat_deg, lon_deg);
+        Self::from_geo(track, lat_deg, lon_deg, alt_meters, speed_ms, heading_deg)
+    }
+
+    /// Decoded latitude in degrees (quantized to the 19-bit field).
+    pub fn lat_deg(&self) -> f64 {
+        self.latitude as f64 / LAT_SCALE - 90.0
+    }
+
+    /// Decoded longitude in degrees (quantized to the 19-bit field).
+    pub fn lon_deg(&self) -> f64 {
+        self.longitude as f64 / LON_SCALE - 180.0
+    }
+
+    /// Decoded altitude in meters (quantized to 25 ft steps).
+    pub fn alt_m(&self) -> f64 {
+        self.altitude as f64 * ALT_STEP / 3.28084
+    }
+
+    /// Inverse of [`from_geo`](Self::from_geo): `(lat_deg, lon_deg, alt_m, speed_ms,
+    /// heading_deg)`.
+    ///
+    /// Lat/lon come back to within half a 19-bit step (about 1.7e-4° lat, 3.4e-4° lon)
+    /// of the packed input, and repacking them gives the same fields. Altitude is rounded
+    /// to whole meters and saturates at `i16::MAX`, below the 14-bit field's ceiling.
+    pub fn to_geo(&self) -> (f64, f64, i16, u16, f32) {
+        (
+            self.lat_deg(),
+            self.lon_deg(),
+            self.alt_m().round() as i16,
+            self.speed_ms,
+            self.heading_cdeg as f32 / 100.0,
+        )
+    }
+}
+
+impl J3_2AirTrack {
+    /// Copy the fields selected by `mask` from a newer report into this track.
+    pub fn merge_from(&mut self, other: &J3_2AirTrack, mask: FieldMask) {
+        if mask.contains(FieldMask::TRACK) {
+            self.track = other.track;
+            self.track_number = other.track_number;
+        }
+        if mask.contains(FieldMask::LATITUDE) {
+            self.latitude = other.latitude;
+        }
+        if mask.contains(FieldMask::LONGITUDE) {
+            self.longitude = other.longitude;
+        }
+        if mask.contains(FieldMask::ALTITUDE) {
+            self.altitude = other.altitude;
+        }
+        if mask.contains(FieldMask::SPEED) {
+            self.speed_ms = other.speed_ms;
+        }
+        if mask.contains(FieldMask::HEADING) {
+            self.heading_cdeg = other.heading_cdeg;
+        }
+    }
+}
+
+impl J3_2AirTrack {
+    /// A synthetic report `t` of the way from `a` to `b` (`t` is clamped to `[0, 1]`),
+    /// keeping `a`'s track numbers. Position follows the great circle between the two
+    /// reports, so tracks crossing the antimeridian take the short way round; altitude and
+    /// speed are linear, and heading turns through the shorter arc.
+    pub fn interpolate(a: &J3_2AirTrack, b: &J3_2AirTrack, t: f64) -> J3_2AirTrack {
+        let t = t.clamp(0.0, 1.0);
+        let (lat_deg, lon_deg) = slerp(
+            (a.lat_deg().to_radians(), a.lon_deg().to_radians()),
+            (b.lat_deg().to_radians(), b.lon_deg().to_radians()),
+            t,
+        );
+        let turn = (b.heading_cdeg as f64 - a.heading_cdeg as f64 + 18_000.0).rem_euclid(36_000.0)
+            - 18_000.0;
+        let heading_cdeg = (a.heading_cdeg as f64 + t * turn)
+            .rem_euclid(36_000.0)
+            .round() as u16;
+        Self {
+            track: a.track,
+            track_number: a.track_number,
+            latitude: ((lat_deg + 90.0) * LAT_SCALE).round() as u32,
+            longitude: ((lon_deg + 180.0) * LON_SCALE).round() as u32,
+            altitude: pack_altitude(lerp(a.alt_m(), b.alt_m(), t)),
+            parity: 0,
+            speed_ms: lerp(a.speed_ms as f64, b.speed_ms as f64, t).round() as u16,
+            heading_cdeg: heading_cdeg % 36_000,
+        }
+    }
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t
+}
+
+/// Spherical interpolation between two `(lat, lon)` points in radians, returning degrees.
+fn slerp((lat1, lon1): (f64, f64), (lat2, lon2): (f64, f64), t: f64) -> (f64, f64) {
+    let unit = |lat: f64, lon: f64| [lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin()];
+    let (p, q) = (unit(lat1, lon1), unit(lat2, lon2));
+    let dot = p[0] * q[0] + p[1] * q[1] + p[2] * q[2];
+    let cross = [
+        p[1] * q[2] - p[2] * q[1],
+        p[2] * q[0] - p[0] * q[2],
+        p[0] * q[1] - p[1] * q[0],
+    ];
+    let angle = (cross[0] * cross[0] + cross[1] * cross[1] + cross[2] * cross[2])
+        .sqrt()
+        .atan2(dot);
+    // Coincident (or antipodal, where no great circle is preferred) points: blend linearly.
+    let (wp, wq) = if angle.sin().abs() < 1e-12 {
+        (1.0 - t, t)
+    } else {
+        (
+            ((1.0 - t) * angle).sin() / angle.sin(),
+            (t * angle).sin() / angle.sin(),
+        )
+    };
+    let v = [0, 1, 2].map(|i| wp * p[i] + wq * q[i]);
+    (
+        v[2].atan2(v[0].hypot(v[1])).to_degrees(),
+        v[1].atan2(v[0]).to_degrees(),
+    )
+}
+
+/// Selects [`J3_2AirTrack`] fields for [`J3_2AirTrack::merge_from`]; combine with `|`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FieldMask(u8);
+
+impl FieldMask {
+    pub const NONE: FieldMask = FieldMask(0);
+    /// `track` together with its derived `track_number`.
+    pub const TRACK: FieldMask = FieldMask(1 << 0);
+    pub const LATITUDE: FieldMask = FieldMask(1 << 1);
+    pub const LONGITUDE: FieldMask = FieldMask(1 << 2);
+    pub const ALTITUDE: FieldMask = FieldMask(1 << 3);
+    pub const SPEED: FieldMask = FieldMask(1 << 4);
+    pub const HEADING: FieldMask = FieldMask(1 << 5);
+    pub const POSITION: FieldMask = FieldMask(Self::LATITUDE.0 | Self::LONGITUDE.0);
+    pub const ALL: FieldMask = FieldMask(0x3F);
+
+    pub fn contains(self, other: FieldMask) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl core::ops::BitOr for FieldMask {
+    type Output = FieldMask;
+
+    fn bitor(self, rhs: FieldMask) -> FieldMask {
+        FieldMask(self.0 | rhs.0)
+    }
+}
+
+impl J3_2AirTrack {
+    /// Checked variant of `from_geo`: fails with `Error::OutOfRange` instead of truncating when
+    /// a scaled value doesn't fit its packed bit width (or the input isn't finite).
+    pub fn try_from_geo(
+        track: u16,
+        lat_deg: f64,
+        lon_deg: f64,
+        alt_meters: f64,
+        speed_ms: u16,
+        heading_deg: u16,
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            track,
+            track_number: track & 0x0FFF,
+            latitude: scale_checked("latitude", lat_deg, 90.0, LAT_SCALE, 19)?,
+            longitude: scale_checked("longitude", lon_deg, 180.0, LON_SCALE, 19)?,
+            altitude: scale_checked("altitude", alt_meters, 0.0, 3.28084 / ALT_STEP, 14)? as u16,
             parity: 0,
             speed_ms,
             heading_cdeg: heading_deg,
+        })
+    }
+
+    /// Check that the decoded position and heading are physically possible. The 19-bit
+    /// lat/lon fields can only exceed their range if set directly in memory; a heading
+    /// above 35999 cdeg can arrive off the wire.
+    pub fn validate(&self) -> Result<(), Error> {
+        check_range("latitude", self.lat_deg(), -90.0, 90.0)?;
+        check_range("longitude", self.lon_deg(), -180.0, 180.0)?;
+        check_range("heading_cdeg", self.heading_cdeg as f64, 0.0, 35_999.0)
+    }
+}
+
+#[cfg(feature = "geojson")]
+impl J3_2AirTrack {
+    /// A GeoJSON `Point` feature for this track. Coordinates are `[lon, lat]` (longitude
+    /// first, as RFC 7946 requires) in degrees; altitude is in meters and heading in degrees,
+    /// carried as properties.
+    pub fn to_geojson(&self) -> serde_json::Value {
+        let (lat, lon, alt_m, speed_ms, heading_deg) = self.to_geo();
+        serde_json::json!({
+            "type": "Feature",
+            "geometry": { "type": "Point", "coordinates": [lon, lat] },
+            "properties": {
+                "track": self.track,
+                "altitude_m": alt_m,
+                "speed_ms": speed_ms,
+                "heading_deg": heading_deg,
+            },
+        })
+    }
+}
+
+/// How long a CoT event from [`J3_2AirTrack::to_cot`] stays valid after its `time`.
+const COT_STALE: Duration = Duration::from_secs(30);
+
+impl J3_2AirTrack {
+    /// A minimal Cursor-on-Target event for TAK clients: uid `J3.2-<track>`, generic
+    /// friendly-air type `a-f-A`, and a `point` with lat/lon in degrees and `hae` in meters.
+    /// `time`/`start` are `now` and `stale` is 30 s later, so output is reproducible.
+    pub fn to_cot(&self, now: SystemTime) -> String {
+        let time = iso8601(now);
+        let stale = iso8601(now + COT_STALE);
+        format!(
+            concat!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+                r#"<event version="2.0" uid="J3.2-{}" type="a-f-A" how="m-g" "#,
+                r#"time="{time}" start="{time}" stale="{stale}">"#,
+                r#"<point lat="{:.6}" lon="{:.6}" hae="{:.1}" ce="9999999.0" le="9999999.0"/>"#,
+                r#"<detail><track course="{:.2}" speed="{}"/></detail>"#,
+                "</event>"
+            ),
+            self.track,
+            self.lat_deg(),
+            self.lon_deg(),
+            self.alt_m(),
+            self.heading_cdeg as f64 / 100.0,
+            self.speed_ms,
+            time = time,
+            stale = stale,
+        )
+    }
+}
+
+/// `YYYY-MM-DDTHH:MM:SSZ` in UTC; times before the epoch clamp to it.
+fn iso8601(t: SystemTime) -> String {
+    let secs = t.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    // Civil-from-days (H. Hinnant), shifted so eras start on March 1st.
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        rem / 3_600,
+        rem / 60 % 60,
+        rem % 60
+    )
+}
+
+impl J3_2AirTrack {
+    /// Named-field alternative to `from_geo`; see [`J3_2AirTrackBuilder`].
+    pub fn builder() -> J3_2AirTrackBuilder {
+        J3_2AirTrackBuilder::default()
+    }
+}
+
+/// Builder for [`J3_2AirTrack`]. Every kinematic field is required; `build` applies the
+/// `from_geo` scaling with the checks of `try_from_geo` and [`J3_2AirTrack::validate`].
+/// The identity only exists in the v2 layout, so it is used by `build_v2` alone.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct J3_2AirTrackBuilder {
+    track: Option<u16>,
+    lat_deg: Option<f64>,
+    lon_deg: Option<f64>,
+    alt_m: Option<f64>,
+    speed_ms: Option<u16>,
+    heading_deg: Option<f64>,
+    identity: Option<Identity>,
+}
+
+impl J3_2AirTrackBuilder {
+    pub fn track(mut self, track: u16) -> Self {
+        self.track = Some(track);
+        self
+    }
+
+    pub fn lat(mut self, lat_deg: f64) -> Self {
+        self.lat_deg = Some(lat_deg);
+        self
+    }
+
+    pub fn lon(mut self, lon_deg: f64) -> Self {
+        self.lon_deg = Some(lon_deg);
+        self
+    }
+
+    pub fn alt_m(mut self, alt_m: f64) -> Self {
+        self.alt_m = Some(alt_m);
+        self
+    }
+
+    pub fn speed_ms(mut self, speed_ms: u16) -> Self {
+        self.speed_ms = Some(speed_ms);
+        self
+    }
+
+    /// Heading in degrees; packed as centidegrees.
+    pub fn heading_deg(mut self, heading_deg: f64) -> Self {
+        self.heading_deg = Some(heading_deg);
+        self
+    }
+
+    pub fn build(self) -> Result<J3_2AirTrack, Error> {
+        let heading_deg = self.heading_deg.ok_or(Error::MissingField("heading_deg"))?;
+        let heading_cdeg = (heading_deg * 100.0).round();
+        if !(0.0..=35_999.0).contains(&heading_cdeg) {
+            return Err(Error::OutOfRange {
+                field: "heading_deg",
+                value: heading_deg,
+            });
         }
+        let track = J3_2AirTrack::try_from_geo(
+            self.track.ok_or(Error::MissingField("track"))?,
+            self.lat_deg.ok_or(Error::MissingField("lat"))?,
+            self.lon_deg.ok_or(Error::MissingField("lon"))?,
+            self.alt_m.ok_or(Error::MissingField("alt_m"))?,
+            self.speed_ms.ok_or(Error::MissingField("speed_ms"))?,
+            heading_cdeg as u16,
+        )?;
+        track.validate()?;
+        Ok(track)
+    }
+
+    pub fn identity(mut self, identity: Identity) -> Self {
+        self.identity = Some(identity);
+        self
+    }
+
+    /// `build`, then attach the identity as a J3.2 v2 body; the identity is required here.
+    pub fn build_v2(self) -> Result<J3_2AirTrackV2, Error> {
+        let identity = self.identity.ok_or(Error::MissingField("identity"))?;
+        Ok(J3_2AirTrackV2::new(self.build()?, identity))
+    }
+}
+
+/// `Error::OutOfRange` unless `lo <= value <= hi`.
+fn check_range(field: &'static str, value: f64, lo: f64, hi: f64) -> Result<(), Error> {
+    if (lo..=hi).contains(&value) {
+        Ok(())
+    } else {
+        Err(Error::OutOfRange { field, value })
+    }
+}
+
+/// `round((value + offset) * scale)` converted via `f64 -> i64 -> u32::try_from`, then bounded
+/// to `bits`.
+fn scale_checked(
+    field: &'static str,
+    value: f64,
+    offset: f64,
+    scale: f64,
+    bits: u32,
+) -> Result<u32, Error> {
+    let scaled = ((value + offset) * scale).round();
+    let out_of_range = Error::OutOfRange { field, value };
+    if !scaled.is_finite() || scaled < i64::MIN as f64 || scaled > i64::MAX as f64 {
+        return Err(out_of_range);
     }
+    u32::try_from(scaled as i64)
+        .ok()
+        .filter(|v| *v < 1 << bits)
+        .ok_or(out_of_range)
 }
 
 impl fmt::Display for J3_2AirTrack {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "Track #{}: [Lat: {}, Lon: {}] Speed: {}m/s, Alt: {}ft",
-            self.track_number, self.latitude, self.longitude, self.speed_ms, self.altitude
+            "Track #{}: [Lat: {:.6}, Lon: {:.6}] Alt: {:.0}m, Speed: {}m/s, Heading: {:.2}deg",
+            self.track_number,
+            self.lat_deg(),
+            self.lon_deg(),
+            self.alt_m(),
+            self.speed_ms,
+            self.heading_cdeg as f64 / 100.0
         )
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Prototype J2.2 PPLI (Precise Participant Location and Identification) body: an
+/// own-ship position report. Positions use the J3.2 19-bit lat/lon and 25 ft altitude
+/// scaling; big-endian, fixed-width like J3.2.
+#[derive(Debug, Clone, PartialEq, Eq, DekuRead, DekuWrite)]
+#[deku(
+    endian = "endian",
+    ctx = "endian: deku::ctx::Endian",
+    ctx_default = "deku::ctx::Endian::Big"
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct J2_2Ppli {
+    #[deku(bytes = 2)]
+    pub track_number: u16,
+    #[deku(bits = 19)]
+    pub latitude: u32,
+    #[deku(bits = 19)]
+    pub longitude: u32,
+    #[deku(bits = 14)]
+    pub altitude: u16,
+    /// Reporting platform type (prototype code, not interpreted by this crate).
+    #[deku(bits = 8)]
+    pub platform_type: u8,
+}
+
+impl J2_2Ppli {
+    /// Encoded body length in bytes (76 bits, padded to a byte boundary).
+    pub const BODY_LEN: usize = 10;
+
+    pub fn from_geo(
+        track_number: u16,
+        lat_deg: f64,
+        lon_deg: f64,
+        alt_meters: f64,
+        platform_type: u8,
+    ) -> Self {
+        Self {
+            track_number,
+            latitude: ((lat_deg + 90.0) * LAT_SCALE).round() as u32, // 19-bit squish
+            longitude: ((lon_deg + 180.0) * LON_SCALE).round() as u32, // 19-bit squish
+            altitude: pack_altitude(alt_meters),
+            platform_type,
+        }
+    }
+
+    /// Decoded latitude in degrees (quantized to the 19-bit field).
+    pub fn lat_deg(&self) -> f64 {
+        self.latitude as f64 / LAT_SCALE - 90.0
+    }
+
+    /// Decoded longitude in degrees (quantized to the 19-bit field).
+    pub fn lon_deg(&self) -> f64 {
+        self.longitude as f64 / LON_SCALE - 180.0
+    }
+
+    /// Decoded altitude in meters (quantized to 25 ft steps).
+    pub fn alt_m(&self) -> f64 {
+        self.altitude as f64 * ALT_STEP / 3.28084
+    }
+}
+
+impl fmt::Display for J2_2Ppli {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "PPLI #{}: [Lat: {:.6}, Lon: {:.6}] Alt: {:.0}m, Platform: {}",
+            self.track_number,
+            self.lat_deg(),
+            self.lon_deg(),
+            self.alt_m(),
+            self.platform_type
+        )
+    }
+}
+
+/// Prototype J3.3 Surface Track body: a maritime track with no altitude. Lat/lon are
+/// signed degrees × 1e7; big-endian, fixed-width like J3.2.
+#[derive(Debug, Clone, PartialEq, Eq, DekuRead, DekuWrite)]
+#[deku(
+    endian = "endian",
+    ctx = "endian: deku::ctx::Endian",
+    ctx_default = "deku::ctx::Endian::Big"
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct J3_3SurfaceTrack {
+    #[deku(bytes = 2)]
+    pub track_number: u16,
+    #[deku(bytes = 4)]
+    pub latitude_e7: i32,
+    #[deku(bytes = 4)]
+    pub longitude_e7: i32,
+    /// Course over ground in centidegrees.
+    #[deku(bytes = 2)]
+    pub course_cdeg: u16,
+    #[deku(bytes = 2)]
+    pub speed_ms: u16,
+}
+
+impl J3_3SurfaceTrack {
+    /// Encoded body length in bytes.
+    pub const BODY_LEN: usize = 14;
+
+    pub fn from_geo(
+        track_number: u16,
+        lat_deg: f64,
+        lon_deg: f64,
+        speed_ms: u16,
+        course_cdeg: u16,
+    ) -> Self {
+        Self {
+            track_number,
+            latitude_e7: (lat_deg * E7).round() as i32,
+            longitude_e7: (lon_deg * E7).round() as i32,
+            course_cdeg,
+            speed_ms,
+        }
+    }
+
+    /// Decoded latitude in degrees.
+    pub fn lat_deg(&self) -> f64 {
+        self.latitude_e7 as f64 / E7
+    }
+
+    /// Decoded longitude in degrees.
+    pub fn lon_deg(&self) -> f64 {
+        self.longitude_e7 as f64 / E7
+    }
+
+    /// Check lat/lon and course ranges; the e7 fields can encode e.g. 200° on the wire.
+    pub fn validate(&self) -> Result<(), Error> {
+        check_range("latitude", self.lat_deg(), -90.0, 90.0)?;
+        check_range("longitude", self.lon_deg(), -180.0, 180.0)?;
+        check_range("course_cdeg", self.course_cdeg as f64, 0.0, 35_999.0)
+    }
+}
+
+impl fmt::Display for J3_3SurfaceTrack {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Surface #{}: [Lat: {:.7}, Lon: {:.7}] Speed: {}m/s, Course: {}cdeg",
+            self.track_number,
+            self.lat_deg(),
+            self.lon_deg(),
+            self.speed_ms,
+            self.course_cdeg
+        )
+    }
+}
+
+/// Track management action carried by J7.0, encoded as one byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[deku(id_type = "u8", endian = "endian", ctx = "endian: deku::ctx::Endian")]
+#[repr(u8)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TrackAction {
+    /// The reporting unit stops reporting the track.
+    #[deku(id = 0)]
+    Drop = 0,
+    /// Reporting responsibility moves to the receiving unit.
+    #[deku(id = 1)]
+    Handover = 1,
+    /// Ask the receiving unit to point its sensors at the track.
+    #[deku(id = 2)]
+    PointerRequest = 2,
+}
+
+impl TrackAction {
+    pub const ALL: [TrackAction; 3] = [
+        TrackAction::Drop,
+        TrackAction::Handover,
+        TrackAction::PointerRequest,
+    ];
+
+    pub fn code(self) -> u8 {
+        self as u8
+    }
+}
+
+impl fmt::Display for TrackAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            TrackAction::Drop => "drop",
+            TrackAction::Handover => "handover",
+            TrackAction::PointerRequest => "pointer_request",
+        };
+        f.write_str(s)
+    }
+}
+
+/// Prototype J7.0 Track Management body: announces a track drop or a transfer of
+/// reporting responsibility. Decoding rejects unknown action bytes.
+#[derive(Debug, Clone, PartialEq, Eq, DekuRead, DekuWrite)]
+#[deku(
+    endian = "endian",
+    ctx = "endian: deku::ctx::Endian",
+    ctx_default = "deku::ctx::Endian::Big"
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct J7_0TrackManagement {
+    pub action: TrackAction,
+    #[deku(bytes = 2)]
+    pub track_number: u16,
+    /// Unit the action is addressed to (the new owner for a handover).
+    #[deku(bytes = 2)]
+    pub receiving_unit: u16,
+}
+
+impl J7_0TrackManagement {
+    /// Encoded body length in bytes.
+    pub const BODY_LEN: usize = 5;
+
+    pub fn new(action: TrackAction, track_number: u16, receiving_unit: u16) -> Self {
+        Self {
+            action,
+            track_number,
+            receiving_unit,
+        }
+    }
+}
+
+impl fmt::Display for J7_0TrackManagement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Track #{} {} -> unit {}",
+            self.track_number, self.action, self.receiving_unit
+        )
+    }
+}
+
+/// Track identity carried by J3.2 v2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Identity {
+    Pending = 0,
+    Unknown = 1,
+    Friend = 2,
+    Neutral = 3,
+    Suspect = 4,
+    Hostile = 5,
+}
+
+impl Identity {
+    pub const ALL: [Identity; 6] = [
+        Identity::Pending,
+        Identity::Unknown,
+        Identity::Friend,
+        Identity::Neutral,
+        Identity::Suspect,
+        Identity::Hostile,
+    ];
+
+    pub fn from_code(code: u8) -> Option<Self> {
+        Self::ALL.get(code as usize).copied()
+    }
+
+    pub fn code(self) -> u8 {
+        self as u8
+    }
+}
+
+impl fmt::Display for Identity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Identity::Pending => "pending",
+            Identity::Unknown => "unknown",
+            Identity::Friend => "friend",
+            Identity::Neutral => "neutral",
+            Identity::Suspect => "suspect",
+            Identity::Hostile => "hostile",
+        };
+        f.write_str(s)
+    }
+}
+
+/// J3.2 v2 body: the J3.2 layout with its 3 trailing pad bits carrying an identity code.
+#[derive(Debug, Clone, PartialEq, Eq, DekuRead, DekuWrite)]
+#[deku(
+    endian = "endian",
+    ctx = "endian: deku::ctx::Endian",
+    ctx_default = "deku::ctx::Endian::Big"
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct J3_2AirTrackV2 {
+    pub base: J3_2AirTrack,
+    /// Raw 3-bit identity code; see [`Identity::from_code`].
+    #[deku(bits = 3)]
+    pub identity_code: u8,
+}
+
+impl J3_2AirTrackV2 {
+    pub fn new(base: J3_2AirTrack, identity: Identity) -> Self {
+        Self {
+            base,
+            identity_code: identity.code(),
+        }
+    }
+
+    /// Decoded identity; unknown codes read as [`Identity::Pending`].
+    pub fn identity(&self) -> Identity {
+        Identity::from_code(self.identity_code).unwrap_or(Identity::Pending)
+    }
+}
+
+/// Seconds in a day; J3.2 v3 times of day must be below this.
+pub const SECONDS_PER_DAY: u32 = 86_400;
+
+/// J3.2 v3 body: the v2 layout followed by a 24-bit report time in seconds since midnight
+/// UTC. Times of day at or past [`SECONDS_PER_DAY`] fail to encode or decode.
+#[derive(Debug, Clone, PartialEq, Eq, DekuRead, DekuWrite)]
+#[deku(
+    endian = "endian",
+    ctx = "endian: deku::ctx::Endian",
+    ctx_default = "deku::ctx::Endian::Big"
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct J3_2AirTrackV3 {
+    pub base: J3_2AirTrackV2,
+    #[deku(bits = 24, assert = "*time_of_day < SECONDS_PER_DAY")]
+    pub time_of_day: u32,
+}
+
+impl J3_2AirTrackV3 {
+    /// Encoded body length in bytes (the 120-bit v2 body plus 24 bits).
+    pub const BODY_LEN: usize = 18;
+
+    /// Attach a report time in seconds since midnight UTC.
+    pub fn new(base: J3_2AirTrackV2, time_of_day: u32) -> Result<Self, Error> {
+        if time_of_day >= SECONDS_PER_DAY {
+            return Err(Error::OutOfRange {
+                field: "time_of_day",
+                value: time_of_day as f64,
+            });
+        }
+        Ok(Self { base, time_of_day })
+    }
+
+    /// Attach a report time, truncated to whole seconds (a leap second reads as :59).
+    #[cfg(feature = "chrono")]
+    pub fn with_time(base: J3_2AirTrackV2, time: chrono::NaiveTime) -> Self {
+        use chrono::Timelike;
+        Self {
+            base,
+            time_of_day: time.num_seconds_from_midnight(),
+        }
+    }
+
+    /// The report time as a `NaiveTime`.
+    #[cfg(feature = "chrono")]
+    pub fn time(&self) -> chrono::NaiveTime {
+        chrono::NaiveTime::from_num_seconds_from_midnight_opt(self.time_of_day, 0)
+            .unwrap_or(chrono::NaiveTime::MIN)
+    }
+}
+
+/// Borrowed counterpart of [`JMessage`] produced by [`JMessage::from_bytes_ref`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JMessageRef<'a> {
+    J3_2(J3_2AirTrackRef<'a>),
+    J3_2V2(J3_2AirTrackRef<'a>),
+}
+
+impl JMessageRef<'_> {
+    /// Materialize an owned [`JMessage`].
+    pub fn to_owned(&self) -> JMessage {
+        match self {
+            JMessageRef::J3_2(v) => JMessage::J3_2(v.to_owned()),
+            JMessageRef::J3_2V2(v) => JMessage::J3_2V2(J3_2AirTrackV2 {
+                base: v.to_owned(),
+                identity_code: v.identity_code(),
+            }),
+        }
+    }
+}
+
+/// Zero-copy view over an encoded J3.2 body; fields are extracted on access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct J3_2AirTrackRef<'a> {
+    body: &'a [u8],
+}
+
+impl<'a> J3_2AirTrackRef<'a> {
+    /// Wrap `body`, which must hold at least [`J3_2AirTrack::BODY_LEN`] bytes.
+    pub fn new(body: &'a [u8]) -> Result<Self, Error> {
+        if body.len() < J3_2AirTrack::BODY_LEN {
+            return Err(Error::Short(body.len()));
+        }
+        Ok(Self {
+            body: &body[..J3_2AirTrack::BODY_LEN],
+        })
+    }
+
+    /// The raw encoded body this view borrows.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.body
+    }
+
+    // Big-endian, MSB-first bit extraction matching the deku layout.
+    fn bits(&self, offset: usize, width: usize) -> u32 {
+        (offset..offset + width).fold(0u32, |acc, i| {
+            let bit = (self.body[i / 8] >> (7 - i % 8)) & 1;
+            (acc << 1) | u32::from(bit)
+        })
+    }
+
+    pub fn track(&self) -> u16 {
+        u16::from_be_bytes([self.body[0], self.body[1]])
+    }
+
+    pub fn latitude(&self) -> u32 {
+        self.bits(16, 19)
+    }
+
+    pub fn longitude(&self) -> u32 {
+        self.bits(35, 19)
+    }
+
+    pub fn track_number(&self) -> u16 {
+        self.bits(54, 12) as u16
+    }
+
+    pub fn altitude(&self) -> u16 {
+        self.bits(66, 14) as u16
+    }
+
+    pub fn parity(&self) -> u8 {
+        self.bits(80, 5) as u8
+    }
+
+    pub fn speed_ms(&self) -> u16 {
+        self.bits(85, 16) as u16
+    }
+
+    pub fn heading_cdeg(&self) -> u16 {
+        self.bits(101, 16) as u16
+    }
+
+    /// J3.2 v2 identity code; always zero padding in a plain J3.2 body.
+    pub fn identity_code(&self) -> u8 {
+        self.bits(117, 3) as u8
+    }
+
+    /// Materialize an owned [`J3_2AirTrack`].
+    pub fn to_owned(&self) -> J3_2AirTrack {
+        J3_2AirTrack {
+            track: self.track(),
+            latitude: self.latitude(),
+            longitude: self.longitude(),
+            track_number: self.track_number(),
+            altitude: self.altitude(),
+            parity: self.parity(),
+            speed_ms: self.speed_ms(),
+            heading_cdeg: self.heading_cdeg(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
     #[test]
     fn roundtrip_j3_2() {
@@ -149,4 +1647,858 @@ mod tests {
         let parsed = JMessage::from_bytes(&bytes).unwrap();
         assert_eq!(msg, parsed);
     }
+
+    #[test]
+    fn roundtrip_j2_2_ppli() {
+        let ppli = J2_2Ppli::from_geo(0x0123, 51.4775, -0.4614, 12_000.0, 7);
+        let msg = JMessage::J2_2(ppli.clone());
+        let bytes = msg.to_bytes().unwrap();
+        assert_eq!(bytes.len(), 1 + J2_2Ppli::BODY_LEN);
+        assert_eq!(bytes[0], MSG_ID_J2_2);
+        assert_eq!(JMessage::from_bytes(&bytes).unwrap(), msg);
+        assert_eq!(msg.category(), MessageCategory::PositionReport);
+        assert_eq!(
+            JMessage::from_bytes_v2(&msg.to_bytes_v2().unwrap()).unwrap(),
+            msg
+        );
+        assert!((ppli.lat_deg() - 51.4775).abs() < 1e-3);
+        assert!((ppli.lon_deg() - -0.4614).abs() < 1e-3);
+    }
+
+    #[test]
+    fn roundtrip_j3_3_surface_track() {
+        let track = J3_3SurfaceTrack::from_geo(0x0456, -33.8568, 151.2153, 9, 27_000);
+        let msg = JMessage::J3_3(track.clone());
+        let bytes = msg.to_bytes().unwrap();
+        assert_eq!(bytes.len(), 1 + J3_3SurfaceTrack::BODY_LEN);
+        assert_eq!(bytes[0], MSG_ID_J3_3);
+        assert_eq!(JMessage::from_bytes(&bytes).unwrap(), msg);
+        assert_eq!(
+            JMessage::from_bytes_v2(&msg.to_bytes_v2().unwrap()).unwrap(),
+            msg
+        );
+        assert_eq!(track.latitude_e7, -338_568_000);
+        assert!((track.lon_deg() - 151.2153).abs() < 1e-7);
+    }
+
+    #[test]
+    fn j3_2_and_j3_3_do_not_cross_parse() {
+        let air = JMessage::J3_2(J3_2AirTrack::from_geo(42, 45.0, -122.0, 1500.0, 220, 271));
+        let surface = JMessage::J3_3(J3_3SurfaceTrack::from_geo(42, 45.0, -122.0, 9, 27_000));
+        let (air_bytes, surface_bytes) = (air.to_bytes().unwrap(), surface.to_bytes().unwrap());
+        assert_eq!(JMessage::from_bytes(&air_bytes).unwrap(), air);
+        assert_eq!(JMessage::from_bytes(&surface_bytes).unwrap(), surface);
+        // A J3.3 body is one byte short of a J3.2 body, so relabelling it can't decode.
+        let mut relabelled = surface_bytes.clone();
+        relabelled[0] = MSG_ID_J3_2;
+        assert!(matches!(
+            JMessage::from_bytes(&relabelled),
+            Err(Error::Deku(_))
+        ));
+        // The versioned framing keeps the kinds apart too.
+        let mut v2 = surface.to_bytes_v2().unwrap();
+        assert_eq!(v2[0], MSG_ID_J3_3);
+        v2[0] = MSG_ID_J3_2;
+        assert!(JMessage::from_bytes_v2(&v2).is_err());
+    }
+
+    #[test]
+    fn roundtrip_j7_0_every_action() {
+        for action in TrackAction::ALL {
+            let msg = JMessage::J7_0(J7_0TrackManagement::new(action, 0x0123, 0x0456));
+            let bytes = msg.to_bytes().unwrap();
+            assert_eq!(bytes, [MSG_ID_J7_0, action.code(), 0x01, 0x23, 0x04, 0x56]);
+            assert_eq!(msg.category(), MessageCategory::Control);
+            assert_eq!(JMessage::from_bytes(&bytes).unwrap(), msg);
+            assert_eq!(
+                JMessage::from_bytes_v2(&msg.to_bytes_v2().unwrap()).unwrap(),
+                msg
+            );
+        }
+    }
+
+    #[test]
+    fn j7_0_rejects_invalid_action_byte() {
+        let bytes = [MSG_ID_J7_0, 3, 0x01, 0x23, 0x04, 0x56];
+        assert!(matches!(JMessage::from_bytes(&bytes), Err(Error::Deku(_))));
+    }
+
+    #[test]
+    fn body_len_matches_encoding_of_every_kind() {
+        let base = J3_2AirTrack::from_geo(1, 0.0, 0.0, 0.0, 0, 0);
+        let messages = [
+            JMessage::J3_2(base.clone()),
+            JMessage::J3_2V2(J3_2AirTrackV2::new(base.clone(), Identity::Friend)),
+            JMessage::J3_2V3(
+                J3_2AirTrackV3::new(J3_2AirTrackV2::new(base, Identity::Friend), 60).unwrap(),
+            ),
+            JMessage::J2_2(J2_2Ppli::from_geo(1, 0.0, 0.0, 0.0, 0)),
+            JMessage::J3_3(J3_3SurfaceTrack::from_geo(1, 0.0, 0.0, 0, 0)),
+            JMessage::J7_0(J7_0TrackManagement::new(TrackAction::Drop, 1, 2)),
+        ];
+        for msg in messages {
+            let len = msg.to_bytes().unwrap().len();
+            assert_eq!(JMessage::body_len(msg.id()), Some(len - 1), "{msg}");
+        }
+        assert_eq!(JMessage::body_len(0x23), None);
+    }
+
+    #[test]
+    fn parse_stream_splits_chained_frames() {
+        let tracks: Vec<_> = (1..=3)
+            .map(|n| JMessage::J3_2(J3_2AirTrack::from_geo(n, 10.0, 20.0, 300.0, 150, 90)))
+            .collect();
+        let mut bytes: Vec<u8> = tracks.iter().flat_map(|m| m.to_bytes().unwrap()).collect();
+        assert_eq!(JMessage::parse_stream(&bytes).unwrap(), tracks);
+
+        let frame_len = 1 + J3_2AirTrack::BODY_LEN;
+        bytes.extend(&tracks[0].to_bytes().unwrap()[..frame_len - 4]);
+        let err = JMessage::parse_stream(&bytes).unwrap_err();
+        assert!(
+            matches!(err, Error::ShortAt { offset, needed }
+                if offset == 3 * frame_len && needed == frame_len),
+            "{err}"
+        );
+
+        let mut iter = JMessageIter::new(&bytes);
+        assert_eq!(iter.by_ref().take(3).filter(Result::is_ok).count(), 3);
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn from_bytes_consumed_reports_frame_length() {
+        let msg = JMessage::J3_2(J3_2AirTrack::from_geo(42, 45.0, -122.0, 1500.0, 220, 271));
+        let mut bytes = msg.to_bytes().unwrap();
+        bytes.extend([0xAA, 0xBB]);
+        let (decoded, consumed) = JMessage::from_bytes_consumed(&bytes).unwrap();
+        assert_eq!(decoded, msg);
+        assert_eq!(consumed, 1 + J3_2AirTrack::BODY_LEN);
+        assert_eq!(&bytes[consumed..], [0xAA, 0xBB]);
+
+        let ppli = JMessage::J2_2(J2_2Ppli::from_geo(1, 0.0, 0.0, 0.0, 0));
+        let (_, consumed) = JMessage::from_bytes_consumed(&ppli.to_bytes().unwrap()).unwrap();
+        assert_eq!(consumed, 1 + J2_2Ppli::BODY_LEN);
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn to_geo_inverts_from_geo(
+            lat in -90.0f64..=90.0,
+            lon in -180.0f64..=180.0,
+            alt in 0.0f64..30_000.0,
+            speed: u16,
+            heading in 0u16..36_000,
+        ) {
+            let packed = J3_2AirTrack::from_geo(7, lat, lon, alt, speed, heading);
+            let (lat2, lon2, alt2, speed2, heading2) = packed.to_geo();
+            proptest::prop_assert!((lat2 - lat).abs() <= 0.5 / LAT_SCALE + 1e-9);
+            proptest::prop_assert!((lon2 - lon).abs() <= 0.5 / LON_SCALE + 1e-9);
+            proptest::prop_assert!((alt2 as f64 - alt).abs() <= ALT_STEP / 3.28084);
+            proptest::prop_assert_eq!(speed2, speed);
+            proptest::prop_assert!((heading2 - heading as f32 / 100.0).abs() < 1e-3);
+            // Decoded values sit on the grid, so repacking them is lossless.
+            let repacked = J3_2AirTrack::from_geo(7, lat2, lon2, packed.alt_m(), speed, heading);
+            proptest::prop_assert_eq!(repacked, packed);
+        }
+    }
+
+    #[test]
+    fn validate_rejects_out_of_range_latitude() {
+        let mut t = J3_2AirTrack::from_geo(1, 0.0, 0.0, 0.0, 0, 0);
+        t.latitude = 600_000;
+        assert!(matches!(
+            t.validate(),
+            Err(Error::OutOfRange {
+                field: "latitude",
+                ..
+            })
+        ));
+        let mut s = J3_3SurfaceTrack::from_geo(1, 0.0, 0.0, 0, 0);
+        s.latitude_e7 = -910_000_000;
+        let bytes = JMessage::J3_3(s).to_bytes().unwrap();
+        assert!(JMessage::from_bytes(&bytes).is_ok());
+        assert!(matches!(
+            JMessage::from_bytes_validated(&bytes),
+            Err(Error::OutOfRange {
+                field: "latitude",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn validate_rejects_out_of_range_longitude() {
+        let mut t = J3_2AirTrack::from_geo(1, 0.0, 0.0, 0.0, 0, 0);
+        t.longitude = 524_288;
+        assert!(matches!(
+            t.validate(),
+            Err(Error::OutOfRange {
+                field: "longitude",
+                ..
+            })
+        ));
+        let mut s = J3_3SurfaceTrack::from_geo(1, 0.0, 0.0, 0, 0);
+        s.longitude_e7 = 2_000_000_000; // 200°
+        assert!(matches!(
+            JMessage::from_bytes_validated(&JMessage::J3_3(s).to_bytes().unwrap()),
+            Err(Error::OutOfRange {
+                field: "longitude",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn validate_rejects_out_of_range_heading() {
+        let ok = J3_2AirTrack::from_geo(1, 90.0, 180.0, 0.0, 0, 35_999);
+        assert!(ok.validate().is_ok());
+        let bytes = JMessage::J3_2(J3_2AirTrack {
+            heading_cdeg: 36_000,
+            ..ok
+        })
+        .to_bytes()
+        .unwrap();
+        assert!(JMessage::from_bytes(&bytes).is_ok());
+        assert!(matches!(
+            JMessage::from_bytes_validated(&bytes),
+            Err(Error::OutOfRange {
+                field: "heading_cdeg",
+                value
+            }) if value == 36_000.0
+        ));
+    }
+
+    #[test]
+    fn display_uses_human_units() {
+        let msg = JMessage::J3_2(J3_2AirTrack::from_geo(
+            42, 45.0, -122.0, 1500.0, 220, 27_100,
+        ));
+        assert_eq!(
+            msg.to_string(),
+            "J3.2 AirTrack: Track #42: [Lat: 44.999914, Lon: -122.000317] Alt: 1501m, \
+             Speed: 220m/s, Heading: 271.00deg"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_json_roundtrip_is_tagged() {
+        let msg = JMessage::J3_2(J3_2AirTrack::from_geo(42, 45.0, -122.0, 1500.0, 220, 271));
+        let json = serde_json::to_string(&msg).unwrap();
+        assert!(json.starts_with(r#"{"type":"J3_2","track":42,"#), "{json}");
+        assert_eq!(serde_json::from_str::<JMessage>(&json).unwrap(), msg);
+
+        let mgmt = JMessage::J7_0(J7_0TrackManagement::new(TrackAction::Handover, 7, 9));
+        let json = serde_json::to_string(&mgmt).unwrap();
+        assert!(json.contains(r#""action":"Handover""#), "{json}");
+        assert_eq!(serde_json::from_str::<JMessage>(&json).unwrap(), mgmt);
+    }
+
+    #[test]
+    fn stream_errors_point_at_the_bad_frame() {
+        let ppli = JMessage::J2_2(J2_2Ppli::from_geo(1, 0.0, 0.0, 0.0, 0))
+            .to_bytes()
+            .unwrap();
+        let mut bytes = ppli.clone();
+        bytes.extend(&ppli[..4]);
+        let err = JMessage::parse_stream(&bytes).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                Error::ShortAt {
+                    offset: 11,
+                    needed: 11
+                }
+            ),
+            "{err}"
+        );
+        assert_eq!(
+            err.to_string(),
+            "frame at byte 11 truncated: needs 11 bytes"
+        );
+
+        let mut bytes = ppli.clone();
+        bytes.extend([MSG_ID_J7_0, 9, 0, 1, 0, 2]); // action byte 9 is invalid
+        let err = JMessage::parse_stream(&bytes).unwrap_err();
+        assert!(matches!(err, Error::DekuAt { offset: 11, .. }), "{err}");
+
+        let mut bytes = ppli;
+        bytes.push(0x23);
+        let err = JMessage::parse_stream(&bytes).unwrap_err();
+        assert!(
+            matches!(&err, Error::At { offset: 11, error } if matches!(**error, Error::Unsupported(0x23))),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn frame_roundtrip_over_a_byte_stream() {
+        let air = JMessage::J3_2(J3_2AirTrack::from_geo(42, 45.0, -122.0, 1500.0, 220, 271));
+        let mgmt = JMessage::J7_0(J7_0TrackManagement::new(TrackAction::Drop, 42, 3));
+        let mut stream = air.to_frame().unwrap();
+        assert_eq!(stream[..2], [0, 1 + J3_2AirTrack::BODY_LEN as u8]);
+        stream.extend(mgmt.to_frame().unwrap());
+
+        let (first, used) = JMessage::from_frame(&stream).unwrap();
+        assert_eq!((first, used), (air, 2 + 1 + J3_2AirTrack::BODY_LEN));
+        let (second, rest) = JMessage::from_frame(&stream[used..]).unwrap();
+        assert_eq!(second, mgmt);
+        assert_eq!(used + rest, stream.len());
+    }
+
+    #[test]
+    fn split_frames_recovers_opaque_records() {
+        let mut pdu = Vec::new();
+        for record in [&b"a"[..], b"", b"sealed bytes"] {
+            push_frame(&mut pdu, record).unwrap();
+        }
+        assert_eq!(
+            split_frames(&pdu).unwrap(),
+            vec![&b"a"[..], b"", b"sealed bytes"]
+        );
+        assert!(matches!(
+            split_frames(&pdu[..pdu.len() - 1]),
+            Err(Error::ShortAt {
+                offset: 5,
+                needed: 14
+            })
+        ));
+        assert!(matches!(
+            push_frame(&mut pdu, &vec![0; 70_000]),
+            Err(Error::ExceedsLimit { len: 70_000, .. })
+        ));
+    }
+
+    #[test]
+    fn frame_length_past_buffer_is_short() {
+        let mut frame = JMessage::J7_0(J7_0TrackManagement::new(TrackAction::Drop, 1, 2))
+            .to_frame()
+            .unwrap();
+        frame[..2].copy_from_slice(&u16::MAX.to_be_bytes());
+        assert!(matches!(JMessage::from_frame(&frame), Err(Error::Short(8))));
+        assert!(matches!(JMessage::from_frame(&[0]), Err(Error::Short(1))));
+    }
+
+    #[test]
+    fn builder_matches_from_geo() {
+        let built = J3_2AirTrack::builder()
+            .track(42)
+            .lat(45.0)
+            .lon(-122.0)
+            .alt_m(1500.0)
+            .speed_ms(220)
+            .heading_deg(2.71)
+            .build()
+            .unwrap();
+        assert_eq!(
+            built,
+            J3_2AirTrack::from_geo(42, 45.0, -122.0, 1500.0, 220, 271)
+        );
+    }
+
+    #[test]
+    fn builder_rejects_missing_and_out_of_range_fields() {
+        let partial = J3_2AirTrack::builder()
+            .track(42)
+            .lat(45.0)
+            .alt_m(1500.0)
+            .speed_ms(220)
+            .heading_deg(90.0);
+        assert!(matches!(partial.build(), Err(Error::MissingField("lon"))));
+        assert!(matches!(
+            partial.lon(-122.0).heading_deg(360.0).build(),
+            Err(Error::OutOfRange {
+                field: "heading_deg",
+                ..
+            })
+        ));
+        assert!(matches!(
+            partial.lon(181.0).build(),
+            Err(Error::OutOfRange {
+                field: "longitude",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn crc32c_check_value() {
+        assert_eq!(crc32c(b"123456789"), 0xE306_9283);
+    }
+
+    #[test]
+    fn crc_trailer_detects_a_flipped_byte() {
+        let msg = JMessage::J3_2(J3_2AirTrack::from_geo(42, 45.0, -122.0, 1500.0, 220, 271));
+        let framed = msg.to_bytes_crc().unwrap();
+        assert_eq!(framed.len(), 1 + J3_2AirTrack::BODY_LEN + 4);
+        assert_eq!(JMessage::from_bytes_crc(&framed).unwrap(), msg);
+
+        for i in 0..framed.len() {
+            let mut corrupted = framed.clone();
+            corrupted[i] ^= 0x01;
+            assert!(
+                matches!(
+                    JMessage::from_bytes_crc(&corrupted),
+                    Err(Error::BadCrc { .. })
+                ),
+                "byte {i}"
+            );
+        }
+        assert!(matches!(
+            JMessage::from_bytes_crc(&[0; 3]),
+            Err(Error::Short(3))
+        ));
+    }
+
+    #[cfg(feature = "geojson")]
+    #[test]
+    fn geojson_point_is_longitude_first() {
+        let t = J3_2AirTrack::from_geo(42, 45.0, -122.0, 1500.0, 220, 27_100);
+        let feature = t.to_geojson();
+        assert_eq!(feature["type"], "Feature");
+        assert_eq!(feature["geometry"]["type"], "Point");
+        let coords = feature["geometry"]["coordinates"].as_array().unwrap();
+        assert_eq!(coords[0].as_f64().unwrap(), t.lon_deg());
+        assert_eq!(coords[1].as_f64().unwrap(), t.lat_deg());
+        assert!((coords[0].as_f64().unwrap() - -122.0).abs() < 1e-3);
+        let props = &feature["properties"];
+        assert_eq!(props["track"], 42);
+        assert_eq!(props["altitude_m"], 1501);
+        assert_eq!(props["speed_ms"], 220);
+        assert_eq!(props["heading_deg"], 271.0);
+    }
+
+    #[test]
+    fn iso8601_formats_utc() {
+        assert_eq!(iso8601(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        let leap_day = UNIX_EPOCH + Duration::from_secs(1_709_210_096);
+        assert_eq!(iso8601(leap_day), "2024-02-29T12:34:56Z");
+    }
+
+    #[test]
+    fn cot_event_is_valid_xml() {
+        let t = J3_2AirTrack::from_geo(42, 45.0, -122.0, 1500.0, 220, 27_100);
+        let now = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let xml = t.to_cot(now);
+        let doc = roxmltree::Document::parse(&xml).unwrap();
+        let event = doc.root_element();
+        assert_eq!(event.tag_name().name(), "event");
+        assert_eq!(event.attribute("uid"), Some("J3.2-42"));
+        assert_eq!(event.attribute("type"), Some("a-f-A"));
+        assert_eq!(event.attribute("time"), Some("2023-11-14T22:13:20Z"));
+        assert_eq!(event.attribute("start"), event.attribute("time"));
+        assert_eq!(event.attribute("stale"), Some("2023-11-14T22:13:50Z"));
+        let point = event.children().find(|n| n.has_tag_name("point")).unwrap();
+        assert_eq!(point.attribute("lat"), Some("44.999914"));
+        assert_eq!(point.attribute("lon"), Some("-122.000317"));
+        assert_eq!(point.attribute("hae"), Some("1501.1"));
+    }
+
+    #[test]
+    fn altitude_quantizes_to_nearest_25_ft_step() {
+        let ft = |feet: f64| feet / 3.28084;
+        assert_eq!(pack_altitude(ft(1000.0)), 40);
+        assert_eq!(pack_altitude(ft(1012.4)), 40);
+        assert_eq!(pack_altitude(ft(1012.6)), 41);
+        assert_eq!(pack_altitude(ft(37.4)), 1);
+        assert_eq!(pack_altitude(ft(37.6)), 2);
+        assert_eq!(pack_altitude(-50.0), 0);
+        assert_eq!(pack_altitude(f64::NAN), 0);
+        assert_eq!(pack_altitude(200_000.0), ALT_MAX);
+
+        let t = J3_2AirTrack::from_geo(0x1ABC, 0.0, 0.0, 200_000.0, 0, 0);
+        assert_eq!((t.altitude, t.track_number), (ALT_MAX, 0xABC));
+        assert!(JMessage::J3_2(t).to_bytes().is_ok());
+    }
+
+    #[test]
+    fn from_geo_imperial_converts_to_si() {
+        let t = J3_2AirTrack::from_geo_imperial(42, 45.0, -122.0, 35_000.0, 450.0, 271.5);
+        assert_eq!(
+            t,
+            J3_2AirTrack::from_geo(42, 45.0, -122.0, 10_668.0, 232, 27_150)
+        );
+        assert_eq!(t.altitude, 1400);
+
+        let heading =
+            |deg| J3_2AirTrack::from_geo_imperial(1, 0.0, 0.0, 0.0, 0.0, deg).heading_cdeg;
+        assert_eq!(heading(360.0), 0);
+        assert_eq!(heading(450.0), 9_000);
+        assert_eq!(heading(-90.0), 27_000);
+    }
+
+    #[test]
+    fn lenient_parse_skips_a_corrupt_frame() {
+        let air = JMessage::J3_2(J3_2AirTrack::from_geo(42, 45.0, -122.0, 1500.0, 220, 271));
+        let ppli = JMessage::J2_2(J2_2Ppli::from_geo(7, 10.0, 20.0, 300.0, 1));
+        let mut bytes = air.to_bytes().unwrap();
+        let bad_at = bytes.len();
+        bytes.extend([MSG_ID_J7_0, 9, 0, 1, 0, 2]); // invalid action byte
+        bytes.extend(ppli.to_bytes().unwrap());
+
+        let (messages, errors) = JMessage::parse_all_lenient(&bytes);
+        assert_eq!(messages, [air, ppli]);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, bad_at);
+        assert!(matches!(errors[0].1, Error::Deku(_)));
+
+        let (messages, errors) = JMessage::parse_all_lenient(&[0x01, 0x02]);
+        assert!(messages.is_empty());
+        assert!(matches!(errors[..], [(0, Error::Unsupported(0x01))]));
+    }
+
+    #[test]
+    fn little_endian_roundtrip() {
+        let base = J3_2AirTrack::from_geo(0x0123, 45.0, -122.0, 1500.0, 220, 27_100);
+        let messages = [
+            JMessage::J3_2(base.clone()),
+            JMessage::J3_2V2(J3_2AirTrackV2::new(base, Identity::Hostile)),
+            JMessage::J2_2(J2_2Ppli::from_geo(0x0123, 10.0, 20.0, 300.0, 4)),
+            JMessage::J3_3(J3_3SurfaceTrack::from_geo(0x0123, -33.0, 151.0, 9, 27_000)),
+            JMessage::J7_0(J7_0TrackManagement::new(TrackAction::Handover, 0x0123, 7)),
+        ];
+        for msg in messages {
+            let le = msg.to_bytes_le().unwrap();
+            assert_eq!(le.len(), msg.to_bytes().unwrap().len(), "{msg}");
+            assert_eq!(JMessage::from_bytes_le(&le).unwrap(), msg);
+        }
+    }
+
+    #[test]
+    fn endianness_mismatch_changes_fields() {
+        let msg = JMessage::J3_3(J3_3SurfaceTrack::from_geo(0x0123, -33.0, 151.0, 9, 27_000));
+        let (be, le) = (msg.to_bytes().unwrap(), msg.to_bytes_le().unwrap());
+        assert_eq!((be[1], be[2]), (0x01, 0x23));
+        assert_eq!((le[1], le[2]), (0x23, 0x01));
+        let JMessage::J3_3(swapped) = JMessage::from_bytes(&le).unwrap() else {
+            panic!("expected J3.3");
+        };
+        assert_eq!(swapped.track_number, 0x2301);
+        assert_eq!(swapped.speed_ms, 9u16.swap_bytes());
+        assert_ne!(JMessage::J3_3(swapped), msg);
+    }
+
+    #[test]
+    fn builder_sets_identity_on_v2() {
+        let builder = J3_2AirTrack::builder()
+            .track(42)
+            .lat(45.0)
+            .lon(-122.0)
+            .alt_m(1500.0)
+            .speed_ms(220)
+            .heading_deg(2.71);
+        assert!(matches!(
+            builder.build_v2(),
+            Err(Error::MissingField("identity"))
+        ));
+        for identity in Identity::ALL {
+            let v2 = builder.identity(identity).build_v2().unwrap();
+            assert_eq!(v2.base, builder.build().unwrap());
+            let bytes = JMessage::J3_2V2(v2.clone()).to_bytes().unwrap();
+            let JMessage::J3_2V2(decoded) = JMessage::from_bytes_strict(&bytes).unwrap() else {
+                panic!("expected J3.2 v2");
+            };
+            assert_eq!((decoded.identity(), decoded), (identity, v2));
+        }
+    }
+
+    #[test]
+    fn roundtrip_j3_2_v3_time_of_day() {
+        let base = J3_2AirTrack::from_geo(42, 45.0, -122.0, 1500.0, 220, 271);
+        let v3 = J3_2AirTrackV3::new(J3_2AirTrackV2::new(base, Identity::Friend), 86_399).unwrap();
+        let msg = JMessage::J3_2V3(v3);
+        let bytes = msg.to_bytes().unwrap();
+        assert_eq!(bytes.len(), 1 + J3_2AirTrackV3::BODY_LEN);
+        assert_eq!(bytes[bytes.len() - 3..], [0x01, 0x51, 0x7F]);
+        assert_eq!(JMessage::from_bytes_strict(&bytes).unwrap(), msg);
+        assert_eq!(
+            JMessage::from_bytes_v2(&msg.to_bytes_v2().unwrap()).unwrap(),
+            msg
+        );
+        assert_eq!(
+            JMessage::from_bytes_le(&msg.to_bytes_le().unwrap()).unwrap(),
+            msg
+        );
+    }
+
+    #[test]
+    fn j3_2_v3_rejects_time_of_day_past_midnight() {
+        let base = J3_2AirTrackV2::new(
+            J3_2AirTrack::from_geo(1, 0.0, 0.0, 0.0, 0, 0),
+            Identity::Unknown,
+        );
+        assert!(matches!(
+            J3_2AirTrackV3::new(base.clone(), SECONDS_PER_DAY),
+            Err(Error::OutOfRange {
+                field: "time_of_day",
+                ..
+            })
+        ));
+        let mut bytes = JMessage::J3_2V3(J3_2AirTrackV3::new(base, 0).unwrap())
+            .to_bytes()
+            .unwrap();
+        let n = bytes.len();
+        bytes[n - 3..].copy_from_slice(&[0x01, 0x51, 0x80]); // 86_400
+        assert!(matches!(JMessage::from_bytes(&bytes), Err(Error::Deku(_))));
+        let bad = J3_2AirTrackV3 {
+            base: J3_2AirTrackV2::new(
+                J3_2AirTrack::from_geo(1, 0.0, 0.0, 0.0, 0, 0),
+                Identity::Unknown,
+            ),
+            time_of_day: 90_000,
+        };
+        assert!(JMessage::J3_2V3(bad).to_bytes().is_err());
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn j3_2_v3_naive_time_roundtrip() {
+        let base = J3_2AirTrackV2::new(
+            J3_2AirTrack::from_geo(1, 0.0, 0.0, 0.0, 0, 0),
+            Identity::Unknown,
+        );
+        let time = chrono::NaiveTime::from_hms_opt(13, 37, 42).unwrap();
+        let v3 = J3_2AirTrackV3::with_time(base, time);
+        assert_eq!(v3.time_of_day, 13 * 3600 + 37 * 60 + 42);
+        assert_eq!(v3.time(), time);
+    }
+
+    #[test]
+    fn unknown_kind_still_rejected() {
+        assert!(matches!(
+            JMessage::from_bytes(&[0x23, 0, 0]),
+            Err(Error::Unsupported(0x23))
+        ));
+    }
+
+    #[test]
+    fn bounded_decode_accepts_within_limit() {
+        let msg = JMessage::J3_2(J3_2AirTrack::from_geo(7, 10.0, 20.0, 300.0, 90, 45));
+        let bytes = msg.to_bytes().unwrap();
+        let decoded = JMessage::from_bytes_bounded(&bytes, J3_2AirTrack::BODY_LEN).unwrap();
+        assert_eq!(decoded, msg);
+    }
+
+    #[test]
+    fn bounded_decode_rejects_oversized_body() {
+        let mut bytes = vec![MSG_ID_J3_2];
+        bytes.resize(1 << 20, 0);
+        assert!(matches!(
+            JMessage::from_bytes_bounded(&bytes, 64),
+            Err(Error::ExceedsLimit { len, max: 64 }) if len == (1 << 20) - 1
+        ));
+    }
+
+    #[test]
+    fn borrowed_view_matches_owned_decode() {
+        let msg = JMessage::J3_2(J3_2AirTrack::from_geo(
+            0xBEEF, -33.8688, 151.2093, 10_000.0, 310, 359,
+        ));
+        let bytes = msg.to_bytes().unwrap();
+        assert_eq!(bytes.len(), 1 + J3_2AirTrack::BODY_LEN);
+
+        let JMessage::J3_2(owned) = JMessage::from_bytes(&bytes).unwrap() else {
+            panic!("expected J3.2");
+        };
+        let JMessageRef::J3_2(view) = JMessage::from_bytes_ref(&bytes).unwrap() else {
+            panic!("expected J3.2");
+        };
+        assert_eq!(view.track(), owned.track);
+        assert_eq!(view.latitude(), owned.latitude);
+        assert_eq!(view.longitude(), owned.longitude);
+        assert_eq!(view.track_number(), owned.track_number);
+        assert_eq!(view.altitude(), owned.altitude);
+        assert_eq!(view.parity(), owned.parity);
+        assert_eq!(view.speed_ms(), owned.speed_ms);
+        assert_eq!(view.heading_cdeg(), owned.heading_cdeg);
+        assert_eq!(view.to_owned(), owned);
+        assert_eq!(JMessage::from_bytes_ref(&bytes).unwrap().to_owned(), msg);
+        assert!(matches!(
+            JMessage::from_bytes_ref(&bytes[..4]),
+            Err(Error::Short(3))
+        ));
+    }
+
+    #[test]
+    fn roundtrip_j3_2_v2_all_identities() {
+        let base = J3_2AirTrack::from_geo(7, 10.0, 20.0, 300.0, 150, 90);
+        for identity in Identity::ALL {
+            let msg = JMessage::J3_2V2(J3_2AirTrackV2::new(base.clone(), identity));
+            let bytes = msg.to_bytes().unwrap();
+            assert_eq!(bytes.len(), 1 + J3_2AirTrack::BODY_LEN);
+            let parsed = JMessage::from_bytes_strict(&bytes).unwrap();
+            assert_eq!(parsed, msg);
+            assert_eq!(JMessage::from_bytes_ref(&bytes).unwrap().to_owned(), msg);
+        }
+    }
+
+    #[test]
+    fn invalid_identity_lenient_vs_strict() {
+        let base = J3_2AirTrack::from_geo(7, 10.0, 20.0, 300.0, 150, 90);
+        let v = J3_2AirTrackV2 {
+            base,
+            identity_code: 7,
+        };
+        let bytes = JMessage::J3_2V2(v).to_bytes().unwrap();
+        let JMessage::J3_2V2(lenient) = JMessage::from_bytes(&bytes).unwrap() else {
+            panic!("expected J3.2 v2");
+        };
+        assert_eq!(lenient.identity(), Identity::Pending);
+        assert!(matches!(
+            JMessage::from_bytes_strict(&bytes),
+            Err(Error::InvalidIdentity(7))
+        ));
+    }
+
+    #[test]
+    fn schema_versions_dispatch() {
+        let base = J3_2AirTrack::from_geo(9, 1.0, 2.0, 100.0, 50, 180);
+        for msg in [
+            JMessage::J3_2(base.clone()),
+            JMessage::J3_2V2(J3_2AirTrackV2::new(base, Identity::Hostile)),
+        ] {
+            let bytes = msg.to_bytes_v2().unwrap();
+            assert_eq!(bytes[0], MSG_ID_J3_2);
+            assert_eq!(JMessage::from_bytes_v2(&bytes).unwrap(), msg);
+        }
+    }
+
+    #[test]
+    fn future_schema_version_rejected() {
+        let msg = JMessage::J3_2(J3_2AirTrack::from_geo(9, 1.0, 2.0, 100.0, 50, 180));
+        let mut bytes = msg.to_bytes_v2().unwrap();
+        bytes[1] = SCHEMA_V3 + 1;
+        assert!(matches!(
+            JMessage::from_bytes_v2(&bytes),
+            Err(Error::UnsupportedSchema { version: 4 })
+        ));
+    }
+
+    #[test]
+    fn interpolate_crosses_antimeridian_the_short_way() {
+        let a = J3_2AirTrack::from_geo(7, 10.0, 179.0, 1000.0, 200, 9_000);
+        let b = J3_2AirTrack::from_geo(7, 10.0, -179.0, 3000.0, 300, 9_000);
+        let mid = J3_2AirTrack::interpolate(&a, &b, 0.5);
+        assert!((mid.lat_deg() - 10.0).abs() < 0.01, "{}", mid.lat_deg());
+        assert!(mid.lon_deg().abs() > 179.9, "{}", mid.lon_deg());
+        assert!((mid.alt_m() - 2000.0).abs() < ALT_STEP, "{}", mid.alt_m());
+        assert_eq!(mid.speed_ms, 250);
+
+        let start = J3_2AirTrack::interpolate(&a, &b, 0.0);
+        assert_eq!((start.latitude, start.longitude), (a.latitude, a.longitude));
+        let end = J3_2AirTrack::interpolate(&a, &b, 1.0);
+        assert_eq!((end.latitude, end.longitude), (b.latitude, b.longitude));
+    }
+
+    #[test]
+    fn interpolate_heading_wraps_through_north() {
+        let a = J3_2AirTrack::from_geo(7, 0.0, 0.0, 0.0, 0, 35_000);
+        let b = J3_2AirTrack::from_geo(7, 0.0, 0.0, 0.0, 0, 1_000);
+        let heading = |t| J3_2AirTrack::interpolate(&a, &b, t).heading_cdeg;
+        assert_eq!(heading(0.25), 35_500);
+        assert_eq!(heading(0.5), 0);
+        assert_eq!(heading(0.75), 500);
+        assert_eq!(J3_2AirTrack::interpolate(&b, &a, 0.5).heading_cdeg, 0);
+    }
+
+    #[test]
+    fn merge_position_preserves_kinematics() {
+        let mut known = J3_2AirTrack::from_geo(42, 45.0, -122.0, 1500.0, 220, 271);
+        let update = J3_2AirTrack::from_geo(99, 45.5, -121.5, 9000.0, 10, 90);
+        known.merge_from(&update, FieldMask::POSITION);
+
+        assert_eq!(known.latitude, update.latitude);
+        assert_eq!(known.longitude, update.longitude);
+        assert_eq!(known.track, 42);
+        assert_eq!(known.speed_ms, 220);
+        assert_eq!(known.heading_cdeg, 271);
+        assert_eq!(
+            known.altitude,
+            J3_2AirTrack::from_geo(42, 0.0, 0.0, 1500.0, 0, 0).altitude
+        );
+
+        known.merge_from(&update, FieldMask::ALL);
+        assert_eq!(known, update);
+    }
+
+    #[test]
+    fn try_from_geo_accepts_normal_values() {
+        let checked = J3_2AirTrack::try_from_geo(42, 45.1234567, -122.9876543, 1500.9, 220, 271);
+        assert_eq!(
+            checked.unwrap(),
+            J3_2AirTrack::from_geo(42, 45.1234567, -122.9876543, 1500.9, 220, 271)
+        );
+        assert!(J3_2AirTrack::try_from_geo(1, 90.0, 180.0, 0.0, 0, 0).is_ok());
+    }
+
+    #[test]
+    fn try_from_geo_rejects_overflow() {
+        let err = J3_2AirTrack::try_from_geo(42, 1e12, 0.0, 0.0, 0, 0).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::OutOfRange {
+                field: "latitude",
+                ..
+            }
+        ));
+        assert!(matches!(
+            J3_2AirTrack::try_from_geo(42, 0.0, -181.0, 0.0, 0, 0),
+            Err(Error::OutOfRange {
+                field: "longitude",
+                ..
+            })
+        ));
+        assert!(matches!(
+            J3_2AirTrack::try_from_geo(42, 0.0, 0.0, f64::NAN, 0, 0),
+            Err(Error::OutOfRange {
+                field: "altitude",
+                ..
+            })
+        ));
+        assert!(matches!(
+            J3_2AirTrack::try_from_geo(42, 0.0, 0.0, 200_000.0, 0, 0),
+            Err(Error::OutOfRange {
+                field: "altitude",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn categories() {
+        let track = J3_2AirTrack::from_geo(1, 0.0, 0.0, 0.0, 0, 0);
+        let v2 = JMessage::J3_2V2(J3_2AirTrackV2::new(track.clone(), Identity::Friend));
+        assert_eq!(
+            JMessage::J3_2(track).category(),
+            MessageCategory::PositionReport
+        );
+        assert_eq!(v2.category(), MessageCategory::PositionReport);
+        // Undecodable passthrough bytes are routed by their id byte alone.
+        let passthrough = [0xEE, 1, 2, 3];
+        assert!(JMessage::from_bytes(&passthrough).is_err());
+        assert_eq!(
+            MessageCategory::from_id(passthrough[0]),
+            MessageCategory::Unknown
+        );
+    }
+
+    #[test]
+    fn coarse_grid_merges_nearby_points() {
+        let coarse = Precision::Grid { meters: 1_000.0 };
+        // ~100 m apart, inside the same 1 km cell.
+        let (a, b) = ((45.1020, -122.9900), (45.1030, -122.9890));
+        let pack = |(lat, lon): (f64, f64), p| {
+            J3_2AirTrack::from_geo_with_precision(1, lat, lon, 0.0, 0, 0, p)
+        };
+        assert_ne!(pack(a, Precision::Full), pack(b, Precision::Full));
+        let (ca, cb) = (pack(a, coarse), pack(b, coarse));
+        assert_eq!((ca.latitude, ca.longitude), (cb.latitude, cb.longitude));
+        assert_eq!(
+            pack(a, Precision::Full),
+            J3_2AirTrack::from_geo(1, a.0, a.1, 0.0, 0, 0)
+        );
+        // Points in different cells stay distinct.
+        assert_ne!(pack((45.2, -122.9900), coarse).latitude, ca.latitude);
+    }
 }
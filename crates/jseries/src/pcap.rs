@@ -0,0 +1,101 @@
+//! Record J-Series frames to a pcap capture for offline analysis in Wireshark.
+
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use pcap_file::pcap::{PcapHeader, PcapPacket};
+use pcap_file::DataLink;
+
+use crate::{Error, JMessage};
+
+/// Writes each message's `to_bytes` encoding as one pcap packet. The payloads aren't IP, so
+/// the capture uses link type `USER0` (147); map it to a dissector in Wireshark's DLT User
+/// preferences.
+pub struct PcapWriter<W: Write> {
+    inner: pcap_file::pcap::PcapWriter<W>,
+}
+
+impl<W: Write> PcapWriter<W> {
+    /// Write the global header and wrap `writer`.
+    pub fn new(writer: W) -> Result<Self, Error> {
+        let header = PcapHeader {
+            datalink: DataLink::USER0,
+            ..Default::default()
+        };
+        let inner = pcap_file::pcap::PcapWriter::with_header(writer, header).map_err(io)?;
+        Ok(Self { inner })
+    }
+
+    /// Append one packet holding exactly the frame bytes of `msg`, timestamped `ts`
+    /// (times before the epoch are recorded as the epoch).
+    pub fn write_message(&mut self, ts: SystemTime, msg: &JMessage) -> Result<(), Error> {
+        let bytes = msg.to_bytes()?;
+        let ts = ts.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let packet = PcapPacket::new(ts, bytes.len() as u32, &bytes);
+        self.inner.write_packet(&packet).map_err(io)?;
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner.into_writer()
+    }
+}
+
+fn io(e: pcap_file::PcapError) -> Error {
+    Error::Io(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{J3_2AirTrack, J7_0TrackManagement, TrackAction};
+    use std::time::Duration;
+
+    /// Just enough of the classic pcap format to check what `PcapWriter` produced:
+    /// `(link type, [(seconds, payload)])`; the magic number gives the byte order.
+    fn parse(capture: &[u8]) -> (u32, Vec<(u32, Vec<u8>)>) {
+        let big = match capture[..4] {
+            [0xa1, 0xb2, 0xc3, 0xd4] => true,
+            [0xd4, 0xc3, 0xb2, 0xa1] => false,
+            _ => panic!("not a pcap file"),
+        };
+        let u32_at = |i: usize| {
+            let b = capture[i..i + 4].try_into().unwrap();
+            if big {
+                u32::from_be_bytes(b)
+            } else {
+                u32::from_le_bytes(b)
+            }
+        };
+        let link_type = u32_at(20);
+        let (mut offset, mut packets) = (24, Vec::new());
+        while offset < capture.len() {
+            let (secs, incl_len) = (u32_at(offset), u32_at(offset + 8) as usize);
+            let data = offset + 16;
+            packets.push((secs, capture[data..data + incl_len].to_vec()));
+            offset = data + incl_len;
+        }
+        (link_type, packets)
+    }
+
+    #[test]
+    fn writes_one_packet_per_message() {
+        let air = JMessage::J3_2(J3_2AirTrack::from_geo(42, 45.0, -122.0, 1500.0, 220, 271));
+        let mgmt = JMessage::J7_0(J7_0TrackManagement::new(TrackAction::Drop, 42, 7));
+        let t0 = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+        let mut w = PcapWriter::new(Vec::new()).unwrap();
+        w.write_message(t0, &air).unwrap();
+        w.write_message(t0 + Duration::from_secs(1), &mgmt).unwrap();
+
+        let (link_type, packets) = parse(&w.into_inner());
+        assert_eq!(link_type, 147);
+        assert_eq!(
+            packets,
+            [
+                (1_700_000_000, air.to_bytes().unwrap()),
+                (1_700_000_001, mgmt.to_bytes().unwrap()),
+            ]
+        );
+    }
+}
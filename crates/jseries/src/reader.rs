@@ -0,0 +1,91 @@
+//! Async reader for `to_frame` records over a byte stream (TCP ingest).
+
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::{Error, JMessage};
+
+/// Yields messages from an `AsyncRead` carrying back-to-back [`JMessage::to_frame`]
+/// records, buffering partial reads until a whole record has arrived.
+#[derive(Debug)]
+pub struct JMessageReader<R> {
+    inner: R,
+    buf: Vec<u8>,
+}
+
+impl<R: AsyncRead + Unpin> JMessageReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            buf: Vec::new(),
+        }
+    }
+
+    /// The next message, or `None` at a clean end of stream. A stream ending mid-record is
+    /// `Error::Short`; a bad record is returned as an error and skipped, since its length
+    /// prefix still delimits it.
+    pub async fn next(&mut self) -> Option<Result<JMessage, Error>> {
+        loop {
+            if let [hi, lo, ..] = self.buf[..] {
+                let end = 2 + usize::from(u16::from_be_bytes([hi, lo]));
+                if self.buf.len() >= end {
+                    let msg = JMessage::from_frame(&self.buf[..end]).map(|(msg, _)| msg);
+                    self.buf.drain(..end);
+                    return Some(msg);
+                }
+            }
+            self.buf.reserve(512);
+            match self.inner.read_buf(&mut self.buf).await {
+                Ok(0) if self.buf.is_empty() => return None,
+                Ok(0) => {
+                    let len = self.buf.len();
+                    self.buf.clear();
+                    return Some(Err(Error::Short(len)));
+                }
+                Ok(_) => {}
+                Err(e) => return Some(Err(Error::Io(e.to_string()))),
+            }
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{J3_2AirTrack, J7_0TrackManagement, TrackAction};
+    use tokio::io::AsyncWriteExt;
+
+    #[tokio::test]
+    async fn reads_frames_split_across_reads() {
+        let air = JMessage::J3_2(J3_2AirTrack::from_geo(42, 45.0, -122.0, 1500.0, 220, 271));
+        let mgmt = JMessage::J7_0(J7_0TrackManagement::new(TrackAction::Handover, 42, 7));
+        let mut bytes = air.to_frame().unwrap();
+        bytes.extend(mgmt.to_frame().unwrap());
+
+        // A 5-byte pipe forces every frame to arrive over several reads.
+        let (mut tx, rx) = tokio::io::duplex(5);
+        let writer = tokio::spawn(async move {
+            for chunk in bytes.chunks(3) {
+                tx.write_all(chunk).await.unwrap();
+            }
+        });
+        let mut reader = JMessageReader::new(rx);
+        assert_eq!(reader.next().await.unwrap().unwrap(), air);
+        assert_eq!(reader.next().await.unwrap().unwrap(), mgmt);
+        writer.await.unwrap();
+        assert!(reader.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn truncated_record_at_eof_is_short() {
+        let frame = JMessage::J7_0(J7_0TrackManagement::new(TrackAction::Drop, 1, 2))
+            .to_frame()
+            .unwrap();
+        let mut reader = JMessageReader::new(&frame[..frame.len() - 1]);
+        assert!(matches!(reader.next().await, Some(Err(Error::Short(7)))));
+        assert!(reader.next().await.is_none());
+    }
+}
@@ -0,0 +1,188 @@
+//! Pluggable message kinds: a [`JBody`] declares its id and codec, a [`Registry`] maps ids
+//! to decoders so new kinds don't need an arm in a central match.
+
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use deku::DekuContainerWrite;
+
+use crate::*;
+
+/// A J-Series message body identified on the wire by its leading `MSG_ID` byte.
+pub trait JBody: Sized {
+    const MSG_ID: u8;
+
+    /// Decode from the bytes after the id byte, returning the body and the bytes it used.
+    fn from_body_bytes(body: &[u8]) -> Result<(Self, usize), Error>;
+
+    fn to_body_bytes(&self) -> Result<Vec<u8>, Error>;
+}
+
+macro_rules! deku_bodies {
+    ($($ty:ty => $id:expr),* $(,)?) => {$(
+        impl JBody for $ty {
+            const MSG_ID: u8 = $id;
+
+            fn from_body_bytes(body: &[u8]) -> Result<(Self, usize), Error> {
+                read_body(body)
+            }
+
+            fn to_body_bytes(&self) -> Result<Vec<u8>, Error> {
+                Ok(DekuContainerWrite::to_bytes(self)?)
+            }
+        }
+    )*};
+}
+
+deku_bodies! {
+    J3_2AirTrack => MSG_ID_J3_2,
+    J3_2AirTrackV2 => MSG_ID_J3_2_V2,
+    J3_2AirTrackV3 => MSG_ID_J3_2_V3,
+    J2_2Ppli => MSG_ID_J2_2,
+    J3_3SurfaceTrack => MSG_ID_J3_3,
+    J7_0TrackManagement => MSG_ID_J7_0,
+}
+
+type Decoder<M> = Box<dyn Fn(&[u8]) -> Result<(M, usize), Error> + Send + Sync>;
+
+/// Id-to-decoder dispatch table producing messages of type `M`.
+pub struct Registry<M> {
+    decoders: BTreeMap<u8, Decoder<M>>,
+}
+
+impl<M> Default for Registry<M> {
+    fn default() -> Self {
+        Self {
+            decoders: BTreeMap::new(),
+        }
+    }
+}
+
+impl<M: 'static> Registry<M> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decode `B::MSG_ID` frames as `B`, wrapped into `M` by `wrap`. Registering an id
+    /// twice replaces the earlier decoder.
+    pub fn register<B: JBody + 'static>(&mut self, wrap: fn(B) -> M) -> &mut Self {
+        let decode = move |body: &[u8]| B::from_body_bytes(body).map(|(b, n)| (wrap(b), n));
+        self.decoders.insert(B::MSG_ID, Box::new(decode));
+        self
+    }
+
+    pub fn contains(&self, kind: u8) -> bool {
+        self.decoders.contains_key(&kind)
+    }
+
+    /// Decode one message, returning it with the bytes consumed (id byte included).
+    pub fn decode(&self, input: &[u8]) -> Result<(M, usize), Error> {
+        let (&kind, body) = input.split_first().ok_or(Error::Short(input.len()))?;
+        let decode = self.decoders.get(&kind).ok_or(Error::Unsupported(kind))?;
+        let (msg, len) = decode(body)?;
+        Ok((msg, 1 + len))
+    }
+}
+
+impl Registry<JMessage> {
+    /// Every kind this crate defines.
+    pub fn standard() -> Self {
+        let mut registry = Self::new();
+        registry
+            .register(JMessage::J3_2)
+            .register(JMessage::J3_2V2)
+            .register(JMessage::J3_2V3)
+            .register(JMessage::J2_2)
+            .register(JMessage::J3_3)
+            .register(JMessage::J7_0);
+        registry
+    }
+
+    /// The shared [`Registry::standard`] table used by [`JMessage::from_bytes`].
+    pub fn global() -> &'static Self {
+        static STANDARD: OnceLock<Registry<JMessage>> = OnceLock::new();
+        STANDARD.get_or_init(Self::standard)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MSG_ID_EXPERIMENTAL: u8 = 0xF0;
+
+    #[derive(Debug, PartialEq)]
+    struct Heartbeat {
+        seq: u16,
+    }
+
+    impl JBody for Heartbeat {
+        const MSG_ID: u8 = MSG_ID_EXPERIMENTAL;
+
+        fn from_body_bytes(body: &[u8]) -> Result<(Self, usize), Error> {
+            let seq = body.get(..2).ok_or(Error::Short(body.len()))?;
+            Ok((
+                Self {
+                    seq: u16::from_be_bytes([seq[0], seq[1]]),
+                },
+                2,
+            ))
+        }
+
+        fn to_body_bytes(&self) -> Result<Vec<u8>, Error> {
+            Ok(self.seq.to_be_bytes().to_vec())
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Message {
+        Standard(JMessage),
+        Heartbeat(Heartbeat),
+    }
+
+    #[test]
+    fn standard_registry_covers_every_kind() {
+        let registry = Registry::standard();
+        for kind in [
+            MSG_ID_J3_2,
+            MSG_ID_J3_2_V2,
+            MSG_ID_J3_2_V3,
+            MSG_ID_J2_2,
+            MSG_ID_J3_3,
+            MSG_ID_J7_0,
+        ] {
+            assert!(registry.contains(kind), "{kind:#04x}");
+        }
+        assert!(!registry.contains(MSG_ID_EXPERIMENTAL));
+    }
+
+    #[test]
+    fn custom_kind_parses_through_registry() {
+        let mut registry = Registry::new();
+        registry
+            .register(Message::Heartbeat)
+            .register(|t: J3_2AirTrack| Message::Standard(JMessage::J3_2(t)));
+
+        let mut bytes = vec![Heartbeat::MSG_ID];
+        bytes.extend(Heartbeat { seq: 513 }.to_body_bytes().unwrap());
+        assert_eq!(
+            registry.decode(&bytes).unwrap(),
+            (Message::Heartbeat(Heartbeat { seq: 513 }), 3)
+        );
+
+        let air = JMessage::J3_2(J3_2AirTrack::from_geo(42, 45.0, -122.0, 1500.0, 220, 271));
+        let (decoded, _) = registry.decode(&air.to_bytes().unwrap()).unwrap();
+        assert_eq!(decoded, Message::Standard(air));
+
+        // Not registered here, and the standard table doesn't know the experimental id.
+        let ppli = JMessage::J2_2(J2_2Ppli::from_geo(1, 0.0, 0.0, 0.0, 0));
+        assert!(matches!(
+            registry.decode(&ppli.to_bytes().unwrap()),
+            Err(Error::Unsupported(MSG_ID_J2_2))
+        ));
+        assert!(matches!(
+            JMessage::from_bytes(&bytes),
+            Err(Error::Unsupported(MSG_ID_EXPERIMENTAL))
+        ));
+    }
+}
@@ -0,0 +1,61 @@
+//! Conversions between the SI units the packed fields use and the imperial/aviation units
+//! operators read (knots, feet, Mach).
+
+/// Meters per second in one knot (1852 m per hour).
+pub const MS_PER_KNOT: f64 = 1852.0 / 3600.0;
+/// Meters in one international foot.
+pub const M_PER_FT: f64 = 0.3048;
+/// Speed of sound at sea level in the ISA standard atmosphere, m/s.
+pub const SPEED_OF_SOUND_MS: f64 = 340.294;
+
+pub fn ms_to_knots(ms: f64) -> f64 {
+    ms / MS_PER_KNOT
+}
+
+pub fn knots_to_ms(knots: f64) -> f64 {
+    knots * MS_PER_KNOT
+}
+
+pub fn m_to_ft(meters: f64) -> f64 {
+    meters / M_PER_FT
+}
+
+pub fn ft_to_m(feet: f64) -> f64 {
+    feet * M_PER_FT
+}
+
+/// Mach number at sea level (ISA); real Mach depends on the local temperature.
+pub fn ms_to_mach(ms: f64) -> f64 {
+    ms / SPEED_OF_SOUND_MS
+}
+
+/// Sea-level (ISA) speed in m/s for a Mach number.
+pub fn mach_to_ms(mach: f64) -> f64 {
+    mach * SPEED_OF_SOUND_MS
+}
+
+/// A heading in degrees as the centidegree J3.2 `heading_cdeg` field, wrapped into
+/// `[0, 360)`.
+pub fn deg_to_cdeg(heading_deg: f64) -> u16 {
+    (heading_deg * 100.0).round().rem_euclid(36_000.0) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_values() {
+        assert!((knots_to_ms(1.0) - 0.514444).abs() < 1e-6);
+        assert!((ms_to_knots(100.0) - 194.384).abs() < 1e-3);
+        assert!((ft_to_m(1.0) - 0.3048).abs() < 1e-12);
+        assert!((m_to_ft(1000.0) - 3280.84).abs() < 1e-2);
+        assert!((ms_to_mach(340.294) - 1.0).abs() < 1e-12);
+        assert!((mach_to_ms(0.8) - 272.2352).abs() < 1e-4);
+        assert!((ms_to_knots(knots_to_ms(450.0)) - 450.0).abs() < 1e-9);
+        assert_eq!(deg_to_cdeg(271.0), 27_100);
+        assert_eq!(deg_to_cdeg(271.504), 27_150);
+        assert_eq!(deg_to_cdeg(359.999), 0);
+        assert_eq!(deg_to_cdeg(-90.0), 27_000);
+    }
+}
@@ -0,0 +1,118 @@
+//! Bit-exact J3.2 conformance vectors: the canonical wire reference for interop.
+//!
+//! Each vector pins the packed fields and the exact `to_bytes` output (message id included).
+//! A failure here means the wire layout changed; update the vectors only for an intended
+//! format change, never to follow a dependency upgrade.
+
+use jseries::{J3_2AirTrack, JMessage};
+
+fn vectors() -> Vec<(&'static str, J3_2AirTrack, &'static str)> {
+    vec![
+        (
+            "all zero",
+            J3_2AirTrack {
+                track: 0,
+                latitude: 0,
+                longitude: 0,
+                track_number: 0,
+                altitude: 0,
+                parity: 0,
+                speed_ms: 0,
+                heading_cdeg: 0,
+            },
+            "32000000000000000000000000000000",
+        ),
+        (
+            "min lat/lon",
+            J3_2AirTrack {
+                track: 1,
+                latitude: 0,
+                longitude: 0,
+                track_number: 1,
+                altitude: 0,
+                parity: 0,
+                speed_ms: 0,
+                heading_cdeg: 0,
+            },
+            "32000100000000000040000000000000",
+        ),
+        (
+            "max lat/lon, every field saturated, max heading",
+            J3_2AirTrack {
+                track: 0xFFFF,
+                latitude: 524_287,
+                longitude: 524_287,
+                track_number: 0xFFF,
+                altitude: 16_383,
+                parity: 0x1F,
+                speed_ms: 0xFFFF,
+                heading_cdeg: 35_999,
+            },
+            "32fffffffffffffffffffffffffc64f8",
+        ),
+        (
+            "typical track",
+            J3_2AirTrack {
+                track: 42,
+                latitude: 393_215,
+                longitude: 84_468,
+                track_number: 42,
+                altitude: 197,
+                parity: 0,
+                speed_ms: 220,
+                heading_cdeg: 271,
+            },
+            "32002abfffe527d00a80c50006e00878",
+        ),
+        (
+            "mid-range fields",
+            J3_2AirTrack {
+                track: 0x0ABC,
+                latitude: 262_144,
+                longitude: 131_072,
+                track_number: 0xABC,
+                altitude: 400,
+                parity: 0,
+                speed_ms: 150,
+                heading_cdeg: 18_000,
+            },
+            "320abc8000080002af01900004b23280",
+        ),
+    ]
+}
+
+#[test]
+fn to_bytes_matches_vectors() {
+    for (name, fields, hex) in vectors() {
+        let bytes = JMessage::J3_2(fields).to_bytes().unwrap();
+        assert_eq!(
+            bytes.iter().map(|b| format!("{b:02x}")).collect::<String>(),
+            hex,
+            "{name}"
+        );
+    }
+}
+
+#[test]
+fn from_bytes_recovers_vector_fields() {
+    for (name, fields, hex) in vectors() {
+        let bytes: Vec<u8> = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect();
+        let JMessage::J3_2(decoded) = JMessage::from_bytes(&bytes).unwrap() else {
+            panic!("{name}: expected J3.2");
+        };
+        assert_eq!(decoded, fields, "{name}");
+    }
+}
+
+#[test]
+fn from_geo_edges_land_on_vector_fields() {
+    let min = J3_2AirTrack::from_geo(1, -90.0, -180.0, 0.0, 0, 0);
+    assert_eq!(min, vectors()[1].1);
+    let max = J3_2AirTrack::from_geo(0, 90.0, 180.0, 0.0, 0, 0);
+    assert_eq!((max.latitude, max.longitude), (524_287, 524_287));
+    let typical = J3_2AirTrack::from_geo(42, 45.0, -122.0, 1500.0, 220, 271);
+    assert_eq!(typical, vectors()[3].1);
+}